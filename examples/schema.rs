@@ -3,7 +3,13 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use automatic_savings::msg::{BalanceResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use automatic_savings::msg::{
+    ApproversResponse, BalanceResponse, DepositsResponse, ExecuteMsg, GuardianResponse,
+    InheritanceResponse, InstantiateMsg, InvestmentsResponse, LockResponse, MigrateMsg,
+    OwnershipResponse, PauseResponse, PotsResponse, ProgressResponse, ProposalsResponse, QueryMsg,
+    RouteResponse, SimulateTransferResponse, StakeResponse, StatsResponse, TokenResponse,
+    WithdrawalsResponse,
+};
 use automatic_savings::state::State;
 
 fn main() {
@@ -15,6 +21,24 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(State), &out_dir);
     export_schema(&schema_for!(BalanceResponse), &out_dir);
+    export_schema(&schema_for!(OwnershipResponse), &out_dir);
+    export_schema(&schema_for!(PauseResponse), &out_dir);
+    export_schema(&schema_for!(LockResponse), &out_dir);
+    export_schema(&schema_for!(ProgressResponse), &out_dir);
+    export_schema(&schema_for!(InheritanceResponse), &out_dir);
+    export_schema(&schema_for!(GuardianResponse), &out_dir);
+    export_schema(&schema_for!(ApproversResponse), &out_dir);
+    export_schema(&schema_for!(ProposalsResponse), &out_dir);
+    export_schema(&schema_for!(DepositsResponse), &out_dir);
+    export_schema(&schema_for!(StatsResponse), &out_dir);
+    export_schema(&schema_for!(PotsResponse), &out_dir);
+    export_schema(&schema_for!(InvestmentsResponse), &out_dir);
+    export_schema(&schema_for!(TokenResponse), &out_dir);
+    export_schema(&schema_for!(RouteResponse), &out_dir);
+    export_schema(&schema_for!(StakeResponse), &out_dir);
+    export_schema(&schema_for!(SimulateTransferResponse), &out_dir);
+    export_schema(&schema_for!(WithdrawalsResponse), &out_dir);
 }
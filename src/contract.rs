@@ -3,20 +3,65 @@ use std::env;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coins, to_binary, BankMsg, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
-    Uint128,
+    from_binary, to_binary, Addr, Api, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut,
+    DistributionMsg, Empty, Env, MessageInfo, Order, Reply, Response, StakingMsg, StdError,
+    StdResult, Storage, SubMsg, SubMsgResult, Timestamp, Uint128, WasmMsg,
 };
+use cw_storage_plus::Bound;
 
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use semver::Version;
 
+use crate::dex::Pair;
 use crate::error::ContractError;
-use crate::msg::{BalanceResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{State, STATE};
+use crate::msg::{
+    ApproverSetMsg, ApproversResponse, BalanceResponse, DepositsResponse, ExecuteMsg,
+    GuardianResponse, InheritanceMsg, InheritanceResponse, InstantiateMsg, Investment,
+    InvestmentsResponse, LockResponse, MigrateMsg, OwnershipResponse, PauseResponse, PenaltyMsg,
+    PotAllocation, PotShare, PotsResponse, ProgressResponse, ProposalsResponse, QueryMsg,
+    ReceiveMsg, RouteResponse, SimulateTransferResponse, SpendingSplitMsg, StakeResponse,
+    StatsResponse, TokenResponse, WithdrawalsResponse,
+};
+use crate::state::{
+    config, config_read, Account, AllocationMode, ApproverChange, ApproverSet, Compounding, Dca,
+    DelayChange, Delegation, DenomStats, DepositRecord, GuardianChange, GuardianRequest,
+    Inheritance, LockPolicy, Overflow, PauseScope, Penalty, PendingOwner, Pot, Proposal,
+    ProposalKind, RecentWithdrawal, Recipient, RoundingMode, SavingsGoal, SpendingSplit, State,
+    SwapOrder, Swapping, Unbonding, WithdrawalRequest, ACCOUNTS, COMPOUNDING, DELEGATED, DEPOSITS,
+    DEPOSIT_SEQ, GUARDIAN_REQUESTS, INHERITANCE_PROPOSALS, INVESTMENTS, LEGACY_STATE, PAUSE,
+    PENDING_OWNER, POTS, PROPOSALS, PROPOSAL_SEQ, ROUTES, STAKERS, STATE, STATS, STRATEGY_SHARES,
+    SWAPPING, TOKENS, WITHDRAWALS, WITHDRAWAL_SEQ,
+};
+use crate::strategy::Strategy;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:automatic-savings";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
-const MAIN_ADDRESS: &str = "wasm1pze5wsf0dg0fa4ysnttugn0m22ssf3t4a9yz3h";
+// first release holding an account per saver
+const MULTI_SAVER_VERSION: &str = "0.2.0";
+// savings rates are expressed in basis points
+const BASIS_POINTS: u128 = 10_000;
+// every account has a default pot that takes what the allocations leave
+const DEFAULT_POT: &str = "general";
+const MAX_POT_NAME_LENGTH: usize = 32;
+// CW20 balances are tracked as coins whose denom is this prefix and the token address
+const CW20_PREFIX: &str = "cw20:";
+// replacing a guardian takes a week
+const GUARDIAN_CHANGE_DELAY: u64 = 7 * 24 * 60 * 60;
+// and so does replacing the approvers
+const APPROVERS_CHANGE_DELAY: u64 = 7 * 24 * 60 * 60;
+// the approvers' threshold limits what is withdrawn within a day
+const APPROVAL_WINDOW: u64 = 24 * 60 * 60;
+// replies to claiming the rewards of a single validator
+const COMPOUND_REPLY_ID: u64 = 1;
+// replies to a single swap
+const SWAP_REPLY_ID: u64 = 2;
+// periods set by users are added to block times, this keeps them far from overflowing
+const MAX_PERIOD: u64 = 100 * 365 * 24 * 60 * 60;
+// pagination
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -26,16 +71,34 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     let state = State {
-        owner: deps.api.addr_validate(MAIN_ADDRESS)?,
+        owner: deps.api.addr_validate(&msg.owner)?,
         amount_received: info.funds.clone(),
-        savings_rate: msg.savings_rate,
+        pause_guardian: None,
+        validator: None,
+        unbonding_period: 0,
+        strategy: None,
     };
+    // the owner's account is opened with the instantiate settings
+    let split = msg
+        .split
+        .map(|split| validate_split(deps.api, split))
+        .transpose()?;
+    let account = new_account(
+        env.block.time,
+        msg.savings_rate,
+        msg.rounding,
+        msg.lock,
+        msg.goal,
+        split,
+    )?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     STATE.save(deps.storage, &state)?;
+    ACCOUNTS.save(deps.storage, &state.owner, &account)?;
 
     Ok(Response::new()
         .add_attribute("action", "instantiate")
-        .add_attribute("rate", "15"))
+        .add_attribute("owner", state.owner.to_string())
+        .add_attribute("rate", account.savings_rate.to_string()))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -45,210 +108,6702 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    check_paused(deps.storage, &msg)?;
+    // any action by an account holder shows they are still around
+    record_activity(deps.storage, &info.sender, env.block.time)?;
+
     match msg {
+        ExecuteMsg::OpenAccount {
+            savings_rate,
+            rounding,
+            lock,
+            goal,
+            split,
+            penalty,
+        } => execute_open_account(
+            deps,
+            env,
+            info,
+            savings_rate,
+            rounding,
+            lock,
+            goal,
+            split,
+            penalty,
+        ),
         ExecuteMsg::Transfer {
             received_funds,
             savings_rate,
-        } => execute_transfer(deps, info, received_funds, savings_rate),
+        } => execute_transfer(deps, env, info, received_funds, savings_rate),
+        ExecuteMsg::Receive(wrapper) => execute_receive(deps, env, info, wrapper),
+        ExecuteMsg::UpdateToken { token, accepted } => {
+            execute_update_token(deps, info, token, accepted)
+        }
         ExecuteMsg::Flush {} => execute_flush(deps, env, info),
+        ExecuteMsg::RequestWithdrawal { amount, denom } => {
+            execute_request_withdrawal(deps, env, info, amount, denom)
+        }
+        ExecuteMsg::ExecuteWithdrawal { id } => execute_execute_withdrawal(deps, env, info, id),
+        ExecuteMsg::CancelWithdrawal { id } => execute_cancel_withdrawal(deps, info, id),
+        ExecuteMsg::UpdateConfig {
+            savings_rate,
+            rounding,
+            withdrawal_delay,
+        } => execute_update_config(deps, env, info, savings_rate, rounding, withdrawal_delay),
+        ExecuteMsg::UpdateSplit { split } => execute_update_split(deps, info, split),
+        ExecuteMsg::UpdateApprovers { approvers } => {
+            execute_update_approvers(deps, env, info, approvers)
+        }
+        ExecuteMsg::ConfirmApprovers {} => execute_confirm_approvers(deps, env, info),
+        ExecuteMsg::ApproveProposal { id } => execute_approve_proposal(deps, env, info, id),
+        ExecuteMsg::RevokeApproval { id } => execute_revoke_approval(deps, env, info, id),
+        ExecuteMsg::EarlyWithdraw {} => execute_early_withdraw(deps, env, info),
+        ExecuteMsg::Approve { owner } => execute_approve(deps, env, info, owner),
+        ExecuteMsg::Reject { owner } => execute_reject(deps, info, owner),
+        ExecuteMsg::ProposeGuardian { guardian } => {
+            execute_propose_guardian(deps, env, info, guardian)
+        }
+        ExecuteMsg::ConfirmGuardian {} => execute_confirm_guardian(deps, env, info),
+        ExecuteMsg::UpdatePenalty { penalty } => execute_update_penalty(deps, env, info, penalty),
+        ExecuteMsg::UpdateInheritance { inheritance } => {
+            execute_update_inheritance(deps, info, inheritance)
+        }
+        ExecuteMsg::ClaimInheritance { owner } => execute_claim_inheritance(deps, env, info, owner),
+        ExecuteMsg::CreatePot { name } => execute_create_pot(deps, info, name),
+        ExecuteMsg::RenamePot { name, new_name } => execute_rename_pot(deps, info, name, new_name),
+        ExecuteMsg::ClosePot { name } => execute_close_pot(deps, info, name),
+        ExecuteMsg::MovePotFunds { from, to, amount } => {
+            execute_move_pot_funds(deps, info, from, to, amount)
+        }
+        ExecuteMsg::SetAllocations { allocations } => {
+            execute_set_allocations(deps, info, allocations)
+        }
+        ExecuteMsg::SetPotTarget { name, target } => {
+            execute_set_pot_target(deps, info, name, target)
+        }
+        ExecuteMsg::SetAllocationMode { mode } => execute_set_allocation_mode(deps, info, mode),
+        ExecuteMsg::Pause { scope } => execute_pause(deps, env, info, scope),
+        ExecuteMsg::Unpause { scope } => execute_unpause(deps, info, scope),
+        ExecuteMsg::SetPauseGuardian { guardian } => {
+            execute_set_pause_guardian(deps, info, guardian)
+        }
+        ExecuteMsg::UpdateStaking {
+            validator,
+            unbonding_period,
+        } => execute_update_staking(deps, env, info, validator, unbonding_period),
+        ExecuteMsg::Unstake { address } => execute_unstake(deps, env, info, address),
+        ExecuteMsg::Compound {} => execute_compound(deps, env),
+        ExecuteMsg::UpdateStrategy { strategy } => {
+            execute_update_strategy(deps, env, info, strategy)
+        }
+        ExecuteMsg::Invest { amount } => execute_invest(deps, env, info, amount),
+        ExecuteMsg::Divest { strategy, shares } => {
+            execute_divest(deps, env, info, strategy, shares)
+        }
+        ExecuteMsg::UpdateRoute {
+            offer_denom,
+            ask_denom,
+            pair,
+        } => execute_update_route(deps, info, offer_denom, ask_denom, pair),
+        ExecuteMsg::UpdateDca { dca } => execute_update_dca(deps, info, dca),
+        ExecuteMsg::Swap {
+            amount,
+            ask_denom,
+            belief_price,
+            max_slippage,
+        } => execute_swap(
+            deps,
+            env,
+            info,
+            amount,
+            ask_denom,
+            belief_price,
+            max_slippage,
+        ),
+        ExecuteMsg::ExecuteSwapOrder {} => execute_swap_order(deps, env, info),
+        ExecuteMsg::CheckSwapOrder {} => execute_check_swap_order(deps, env, info),
+        ExecuteMsg::ProposeOwner {
+            new_owner,
+            expires_in,
+        } => execute_propose_owner(deps, env, info, new_owner, expires_in),
+        ExecuteMsg::AcceptOwnership {} => execute_accept_ownership(deps, env, info),
+        ExecuteMsg::CancelOwnershipTransfer {} => execute_cancel_ownership_transfer(deps, info),
     }
 }
 
-pub fn execute_transfer(
+#[allow(clippy::too_many_arguments)]
+pub fn execute_open_account(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    received_funds: Coin,
-    savings_rate: u8,
+    savings_rate: u16,
+    rounding: Option<RoundingMode>,
+    lock: Option<LockPolicy>,
+    goal: Option<Coin>,
+    split: Option<SpendingSplitMsg>,
+    penalty: Option<PenaltyMsg>,
 ) -> Result<Response, ContractError> {
-    STATE.load(deps.storage)?;
+    // one account per address
+    if ACCOUNTS.may_load(deps.storage, &info.sender)?.is_some() {
+        return Err(ContractError::AccountExists {});
+    }
+    let split = split
+        .map(|split| validate_split(deps.api, split))
+        .transpose()?;
+    let mut account = new_account(env.block.time, savings_rate, rounding, lock, goal, split)?;
+    account.penalty = penalty
+        .map(|penalty| validate_penalty(deps.api, &info.sender, penalty))
+        .transpose()?;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "open_account")
+        .add_attribute("saver", info.sender)
+        .add_attribute("rate", account.savings_rate.to_string()))
+}
 
+fn new_account(
+    now: Timestamp,
+    savings_rate: u16,
+    rounding: Option<RoundingMode>,
+    lock: Option<LockPolicy>,
+    goal: Option<Coin>,
+    split: Option<SpendingSplit>,
+) -> Result<Account, ContractError> {
     // valid saving amount
-    if savings_rate > 100 || savings_rate == 0 {
-        return Err(ContractError::InvalidSavingsRate {});
+    validate_savings_rate(savings_rate)?;
+    // a goal has to be worth saving for
+    if let Some(target) = &goal {
+        if target.amount.is_zero() {
+            return Err(ContractError::InvalidGoal {});
+        }
     }
-    // only owner can transfer
-    if String::from(info.sender) != String::from(MAIN_ADDRESS) {
-        return Err(ContractError::Unauthorized {});
+    Ok(Account {
+        savings_rate,
+        rounding: rounding.unwrap_or_default(),
+        lock,
+        goal: goal.map(|target| SavingsGoal {
+            target,
+            reached: false,
+        }),
+        split,
+        allocation: AllocationMode::Fixed,
+        withdrawal_delay: 0,
+        pending_withdrawal_delay: None,
+        inheritance: None,
+        penalty: None,
+        guardian: None,
+        pending_guardian: None,
+        approvers: None,
+        pending_approvers: None,
+        recent_withdrawals: vec![],
+        last_activity: now,
+        delegation: None,
+        unbonding: vec![],
+        dca: None,
+        balance: vec![],
+    })
+}
+
+// Refuse messages that move funds in a paused direction
+fn check_paused(storage: &dyn Storage, msg: &ExecuteMsg) -> Result<(), ContractError> {
+    let pause = match PAUSE.may_load(storage)? {
+        Some(pause) => pause,
+        None => return Ok(()),
+    };
+    let blocked = match msg {
+        ExecuteMsg::Transfer { .. } | ExecuteMsg::Receive(_) => pause.deposits,
+        ExecuteMsg::Flush {}
+        | ExecuteMsg::ExecuteWithdrawal { .. }
+        | ExecuteMsg::EarlyWithdraw {}
+        | ExecuteMsg::Approve { .. }
+        | ExecuteMsg::ApproveProposal { .. }
+        | ExecuteMsg::ClaimInheritance { .. }
+        | ExecuteMsg::Unstake { .. }
+        | ExecuteMsg::Compound {}
+        | ExecuteMsg::Invest { .. }
+        | ExecuteMsg::Divest { .. }
+        | ExecuteMsg::Swap { .. } => pause.withdrawals,
+        _ => false,
+    };
+    if blocked {
+        return Err(ContractError::Paused {});
+    }
+    Ok(())
+}
+
+fn record_activity(storage: &mut dyn Storage, sender: &Addr, now: Timestamp) -> StdResult<()> {
+    if let Some(mut account) = ACCOUNTS.may_load(storage, sender)? {
+        account.last_activity = now;
+        ACCOUNTS.save(storage, sender, &account)?;
+    }
+    // a saver who is still around can't be inherited from
+    if let Some(id) = INHERITANCE_PROPOSALS.may_load(storage, sender)? {
+        INHERITANCE_PROPOSALS.remove(storage, sender);
+        PROPOSALS.remove(storage, id);
+    }
+    Ok(())
+}
+
+fn validate_split(api: &dyn Api, split: SpendingSplitMsg) -> Result<SpendingSplit, ContractError> {
+    if split.recipients.is_empty() {
+        return Err(ContractError::InvalidSplit {
+            reason: "no recipients".to_string(),
+        });
+    }
+    let mut total: u128 = 0;
+    let mut recipients = vec![];
+    for recipient in split.recipients {
+        if recipient.weight == 0 {
+            return Err(ContractError::InvalidSplit {
+                reason: format!("{} has no weight", recipient.address),
+            });
+        }
+        total += u128::from(recipient.weight);
+        recipients.push(Recipient {
+            address: api.addr_validate(&recipient.address)?,
+            weight: recipient.weight,
+        });
+    }
+    // weights must cover exactly 100%
+    if total != BASIS_POINTS {
+        return Err(ContractError::InvalidSplit {
+            reason: format!("weights add up to {} basis points", total),
+        });
+    }
+    let dust_recipient = api.addr_validate(&split.dust_recipient)?;
+    if !recipients.iter().any(|r| r.address == dust_recipient) {
+        return Err(ContractError::InvalidSplit {
+            reason: "dust recipient is not a recipient".to_string(),
+        });
+    }
+    Ok(SpendingSplit {
+        recipients,
+        dust_recipient,
+    })
+}
+
+// Native coins go in a single bank send, each CW20 token in its own transfer
+fn send_msgs(to: &Addr, amount: Vec<Coin>) -> StdResult<Vec<CosmosMsg>> {
+    let (tokens, native): (Vec<Coin>, Vec<Coin>) = amount
+        .into_iter()
+        .partition(|coin| coin.denom.starts_with(CW20_PREFIX));
+    let mut msgs = vec![];
+    if !native.is_empty() {
+        msgs.push(
+            BankMsg::Send {
+                to_address: to.to_string(),
+                amount: native,
+            }
+            .into(),
+        );
+    }
+    for token in tokens {
+        let transfer = Cw20ExecuteMsg::Transfer {
+            recipient: to.to_string(),
+            amount: token.amount,
+        };
+        msgs.push(cw20_msg(&token.denom, transfer)?);
+    }
+    Ok(msgs)
+}
+
+fn burn_msgs(amount: Vec<Coin>) -> StdResult<Vec<CosmosMsg>> {
+    let (tokens, native): (Vec<Coin>, Vec<Coin>) = amount
+        .into_iter()
+        .partition(|coin| coin.denom.starts_with(CW20_PREFIX));
+    let mut msgs = vec![];
+    if !native.is_empty() {
+        msgs.push(BankMsg::Burn { amount: native }.into());
+    }
+    for token in tokens {
+        let burn = Cw20ExecuteMsg::Burn {
+            amount: token.amount,
+        };
+        msgs.push(cw20_msg(&token.denom, burn)?);
+    }
+    Ok(msgs)
+}
+
+fn cw20_msg(denom: &str, msg: Cw20ExecuteMsg) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: denom.trim_start_matches(CW20_PREFIX).to_string(),
+        msg: to_binary(&msg)?,
+        funds: vec![],
+    }
+    .into())
+}
+
+// Share the payout between the split recipients, or send it all to the saver
+fn payout_msgs(
+    split: &Option<SpendingSplit>,
+    saver: &Addr,
+    payout: Vec<Coin>,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let split = match split {
+        Some(split) => split,
+        None => return Ok(send_msgs(saver, payout)?),
+    };
+
+    let mut shares: Vec<Vec<Coin>> = vec![vec![]; split.recipients.len()];
+    for coin in payout.iter() {
+        let mut dust = coin.amount;
+        for (recipient, share) in split.recipients.iter().zip(shares.iter_mut()) {
+            let amount = coin
+                .amount
+                .checked_mul(Uint128::from(recipient.weight))?
+                .u128()
+                / BASIS_POINTS;
+            dust = dust.checked_sub(Uint128::new(amount))?;
+            share.push(Coin {
+                denom: coin.denom.clone(),
+                amount: Uint128::new(amount),
+            });
+        }
+        let dust_share = split
+            .recipients
+            .iter()
+            .position(|r| r.address == split.dust_recipient)
+            .and_then(|i| shares[i].last_mut());
+        if let Some(dust_share) = dust_share {
+            dust_share.amount = dust_share.amount.checked_add(dust)?;
+        }
+    }
+
+    let mut msgs = vec![];
+    for (recipient, share) in split.recipients.iter().zip(shares) {
+        let amount: Vec<Coin> = share.into_iter().filter(|c| !c.amount.is_zero()).collect();
+        if !amount.is_empty() {
+            msgs.extend(send_msgs(&recipient.address, amount)?);
+        }
     }
+    Ok(msgs)
+}
+
+fn load_account(storage: &dyn Storage, saver: &Addr) -> Result<Account, ContractError> {
+    ACCOUNTS
+        .may_load(storage, saver)?
+        .ok_or(ContractError::AccountNotFound {})
+}
+
+pub fn execute_transfer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received_funds: Option<Vec<Coin>>,
+    savings_rate: Option<u16>,
+) -> Result<Response, ContractError> {
+    // only account holders can transfer
+    let account = load_account(deps.storage, &info.sender)?;
+
+    // the stored rate applies unless this transfer overrides it
+    let savings_rate = savings_rate.unwrap_or(account.savings_rate);
+    validate_savings_rate(savings_rate)?;
     //amount received has to be greater than 0
-    if received_funds.amount <= Uint128::from(0 as u32) {
+    if info.funds.iter().all(|fund| fund.amount.is_zero()) {
         return Err(ContractError::EmptyTransfer {});
     }
+    // native denoms can't pass for CW20 tokens
+    if let Some(fund) = info
+        .funds
+        .iter()
+        .find(|fund| fund.denom.starts_with(CW20_PREFIX))
+    {
+        return Err(ContractError::InvalidDenom {
+            denom: fund.denom.clone(),
+        });
+    }
+    // the declared funds, if any, must be exactly what was attached
+    if let Some(expected) = received_funds {
+        if !funds_match(&expected, &info.funds) {
+            return Err(ContractError::FundsMismatch {
+                expected,
+                received: info.funds,
+            });
+        }
+    }
+
+    save_funds(deps, env, &info.sender, account, &info.funds, savings_rate)
+}
 
-    let saved = u128::from(100 - savings_rate);
+// Called by a CW20 contract after the saver sent tokens to this contract
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    // only accepted tokens, anyone can call the hook
+    if !TOKENS.has(deps.storage, &info.sender) {
+        return Err(ContractError::TokenNotAccepted {
+            token: info.sender.to_string(),
+        });
+    }
+    let saver = deps.api.addr_validate(&wrapper.sender)?;
+    // load after recording, so the saved account keeps the new activity
+    record_activity(deps.storage, &saver, env.block.time)?;
+    let account = load_account(deps.storage, &saver)?;
 
-    let send_amount = (saved * u128::from(received_funds.amount)) / u128::from(100 as u32);
-    let send = coins(send_amount, received_funds.denom);
+    let ReceiveMsg::Transfer { savings_rate } = from_binary(&wrapper.msg)?;
+    let savings_rate = savings_rate.unwrap_or(account.savings_rate);
+    validate_savings_rate(savings_rate)?;
+    if wrapper.amount.is_zero() {
+        return Err(ContractError::EmptyTransfer {});
+    }
+    // the sending contract is the token
+    let funds = vec![Coin {
+        denom: format!("{}{}", CW20_PREFIX, info.sender),
+        amount: wrapper.amount,
+    }];
 
-    Ok(Response::new()
-        .add_message(BankMsg::Send {
-            to_address: MAIN_ADDRESS.to_string(),
-            amount: send,
-        })
-        .add_attribute("action", "transfer"))
+    save_funds(deps, env, &saver, account, &funds, savings_rate)
 }
-pub fn execute_flush(
+
+pub fn execute_update_token(
     deps: DepsMut,
-    env: Env,
     info: MessageInfo,
+    token: String,
+    accepted: bool,
 ) -> Result<Response, ContractError> {
     let state = STATE.load(deps.storage)?;
-    // only owner can flush
-    if String::from(info.sender) != MAIN_ADDRESS {
+    // only owner can set the tokens
+    if info.sender != state.owner {
         return Err(ContractError::Unauthorized {});
     }
-
-    let balance = deps.querier.query_all_balances(&env.contract.address)?;
-    // can't flush empty balance
-    if balance.is_empty() {
-        return Err(ContractError::EmptyBalance {});
+    let token = deps.api.addr_validate(&token)?;
+    if accepted {
+        TOKENS.save(deps.storage, &token, &Empty {})?;
+    } else {
+        TOKENS.remove(deps.storage, &token);
     }
+
     Ok(Response::new()
-        .add_message(BankMsg::Send {
-            to_address: MAIN_ADDRESS.to_string(),
-            amount: balance,
-        })
-        .add_attribute("action", "flush"))
+        .add_attribute("action", "update_token")
+        .add_attribute("token", token)
+        .add_attribute("accepted", accepted.to_string()))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetBalance {} => to_binary(&query_balance(deps, env)?),
+// Split funds between savings and payout, the shared part of every deposit
+fn save_funds(
+    mut deps: DepsMut,
+    env: Env,
+    saver: &Addr,
+    mut account: Account,
+    funds: &[Coin],
+    savings_rate: u16,
+) -> Result<Response, ContractError> {
+    let mut send: Vec<Coin> = vec![];
+    let mut returned: Vec<Coin> = vec![];
+    let mut saved: Vec<Coin> = vec![];
+    for fund in funds.iter().filter(|fund| !fund.amount.is_zero()) {
+        let payout = payout_amount(fund.amount, savings_rate, &account.rounding)?;
+        let overflow = credit_savings(
+            deps.storage,
+            saver,
+            &mut account,
+            &Coin {
+                denom: fund.denom.clone(),
+                amount: fund.amount.checked_sub(payout)?,
+            },
+        )?;
+        // savings past every waterfall target go back to the saver
+        let saved_amount = fund.amount.checked_sub(payout)?.checked_sub(overflow)?;
+        let amount = payout.checked_add(overflow)?;
+        record_deposit(
+            deps.storage,
+            saver,
+            DepositRecord {
+                id: 0,
+                timestamp: env.block.time,
+                height: env.block.height,
+                denom: fund.denom.clone(),
+                gross_amount: fund.amount,
+                saved_amount,
+                paid_out_amount: amount,
+                savings_rate,
+            },
+        )?;
+        update_stats(deps.storage, saver, &fund.denom, |stats| {
+            stats.total_received = stats.total_received.checked_add(fund.amount)?;
+            stats.total_saved = stats.total_saved.checked_add(saved_amount)?;
+            stats.total_paid_out = stats.total_paid_out.checked_add(amount)?;
+            stats.transfers += 1;
+            Ok(())
+        })?;
+        if !payout.is_zero() {
+            send.push(Coin {
+                denom: fund.denom.clone(),
+                amount: payout,
+            });
+        }
+        if !overflow.is_zero() {
+            returned.push(Coin {
+                denom: fund.denom.clone(),
+                amount: overflow,
+            });
+        }
+        if !saved_amount.is_zero() {
+            saved.push(Coin {
+                denom: fund.denom.clone(),
+                amount: saved_amount,
+            });
+        }
     }
+
+    let mut res = Response::new()
+        .add_attribute("action", "transfer")
+        .add_attribute("rate", savings_rate.to_string());
+    // swaps go first, so no payout moves the balances their replies measure,
+    // and wait for withdrawals to resume like any other swap
+    let withdrawals_paused = PAUSE.may_load(deps.storage)?.is_some_and(|p| p.withdrawals);
+    if let Some(dca) = account.dca.clone().filter(|_| !withdrawals_paused) {
+        for offer in saved
+            .into_iter()
+            .filter(|offer| offer.denom != dca.ask_denom)
+        {
+            // denoms without a route stay saved as they are
+            if ROUTES
+                .may_load(deps.storage, (&offer.denom, &dca.ask_denom))?
+                .is_some()
+            {
+                res = res.add_submessage(swap_order(
+                    deps.branch(),
+                    &env,
+                    saver,
+                    &mut account,
+                    offer,
+                    &dca.ask_denom,
+                    dca.belief_price,
+                    dca.max_slippage,
+                )?);
+            }
+        }
+    }
+    if !send.is_empty() {
+        res = res.add_messages(payout_msgs(&account.split, saver, send)?);
+    }
+    if !returned.is_empty() {
+        res = res.add_messages(send_msgs(saver, returned)?);
+    }
+
+    // release the savings once the goal is met
+    // a reached goal waits for withdrawals to resume and for the lock to open,
+    // and with a withdrawal delay or approvers it has to be withdrawn like any savings
+    let held =
+        check_unlocked(&account, &env).is_err() || account.withdrawal_delay_at(env.block.time) > 0;
+    let mut released = None;
+    if let Some(goal) = account
+        .goal
+        .as_mut()
+        .filter(|_| !withdrawals_paused && !held)
+    {
+        let saved = account
+            .balance
+            .iter()
+            .find(|saved| saved.denom == goal.target.denom)
+            .cloned();
+        if let Some(saved) = saved {
+            let recent = &account.recent_withdrawals;
+            let gated = account.approvers.as_ref().is_some_and(|set| {
+                needs_approval(set, recent, env.block.time, std::slice::from_ref(&saved))
+            });
+            if !goal.reached && saved.amount >= goal.target.amount && !gated {
+                goal.reached = true;
+                released = Some(saved);
+            }
+        }
+    }
+    if let Some(saved) = released {
+        debit_savings(deps.storage, saver, &mut account, &saved)?;
+        record_withdrawal(&mut account, env.block.time, &saved);
+        update_stats(deps.storage, saver, &saved.denom, |stats| {
+            stats.total_withdrawn = stats.total_withdrawn.checked_add(saved.amount)?;
+            Ok(())
+        })?;
+        res = res
+            .add_attribute("goal_reached", saved.amount.to_string())
+            .add_messages(send_msgs(saver, vec![saved])?);
+    }
+    res = res.add_messages(rebalance_stake(deps.branch(), &env, saver, &mut account)?);
+    ACCOUNTS.save(deps.storage, saver, &account)?;
+
+    Ok(res)
 }
 
-fn query_balance(deps: Deps, env: Env) -> StdResult<BalanceResponse> {
-    let balance = deps.querier.query_all_balances(&env.contract.address)?;
-    Ok(BalanceResponse { balance })
+// Delegate the liquid savings of a locked account, or start unbonding them
+// once the unlock is within the unbonding period. Height locks and goals in
+// the bonded denom can open at any time, so their savings stay liquid
+fn rebalance_stake(
+    deps: DepsMut,
+    env: &Env,
+    saver: &Addr,
+    account: &mut Account,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    account
+        .unbonding
+        .retain(|unbonding| unbonding.completes_at > env.block.time);
+    let state = STATE.load(deps.storage)?;
+    let unlocks_at = match &account.lock {
+        Some(LockPolicy::AtTime(time)) => *time,
+        _ => return Ok(vec![]),
+    };
+    if unlocks_at <= env.block.time.plus_seconds(state.unbonding_period) {
+        return Ok(unstake(deps, env, saver, account, state.unbonding_period)?
+            .into_iter()
+            .collect());
+    }
+    let validator = match state.validator {
+        Some(validator) => validator,
+        None => return Ok(vec![]),
+    };
+    let denom = deps.querier.query_bonded_denom()?;
+    if account
+        .goal
+        .as_ref()
+        .is_some_and(|goal| !goal.reached && goal.target.denom == denom)
+    {
+        return Ok(vec![]);
+    }
+    let amount = account.liquid_of(&denom, env.block.time);
+    if amount.is_zero() {
+        return Ok(vec![]);
+    }
+    // savings keep going to the validator they were first delegated to
+    let delegation = account.delegation.get_or_insert(Delegation {
+        validator,
+        amount: Coin {
+            denom: denom.clone(),
+            amount: Uint128::zero(),
+        },
+    });
+    delegation.amount.amount = delegation.amount.amount.checked_add(amount)?;
+    STAKERS.save(deps.storage, (&delegation.validator, saver), &Empty {})?;
+    DELEGATED.update(
+        deps.storage,
+        &delegation.validator,
+        |total| -> StdResult<_> { Ok(total.unwrap_or_default().checked_add(amount)?) },
+    )?;
+    Ok(vec![StakingMsg::Delegate {
+        validator: delegation.validator.clone(),
+        amount: Coin { denom, amount },
+    }
+    .into()])
 }
 
-#[cfg(test)]
-mod tests {
+// Undelegate everything the account has delegated. After a slash the validator
+// holds less than was recorded, each account loses its share of the difference
+fn unstake(
+    deps: DepsMut,
+    env: &Env,
+    saver: &Addr,
+    account: &mut Account,
+    unbonding_period: u64,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    let delegation = match account.delegation.take() {
+        Some(delegation) => delegation,
+        None => return Ok(None),
+    };
+    STAKERS.remove(deps.storage, (&delegation.validator, saver));
+    let recorded = DELEGATED
+        .may_load(deps.storage, &delegation.validator)?
+        .unwrap_or_default();
+    let bonded = deps
+        .querier
+        .query_delegation(&env.contract.address, &delegation.validator)?
+        .map_or(Uint128::zero(), |bonded| bonded.amount.amount);
+    let amount = if bonded < recorded {
+        delegation.amount.amount.multiply_ratio(bonded, recorded)
+    } else {
+        delegation.amount.amount
+    };
+    DELEGATED.save(
+        deps.storage,
+        &delegation.validator,
+        &recorded.saturating_sub(delegation.amount.amount),
+    )?;
+    let slashed = delegation.amount.amount.checked_sub(amount)?;
+    if !slashed.is_zero() {
+        let slashed = Coin {
+            denom: delegation.amount.denom.clone(),
+            amount: slashed,
+        };
+        debit_savings(deps.storage, saver, account, &slashed)?;
+    }
+    if amount.is_zero() {
+        return Ok(None);
+    }
+    let amount = Coin {
+        denom: delegation.amount.denom,
+        amount,
+    };
+    account.unbonding.push(Unbonding {
+        amount: amount.clone(),
+        completes_at: env.block.time.plus_seconds(unbonding_period),
+    });
+    Ok(Some(
+        StakingMsg::Undelegate {
+            validator: delegation.validator,
+            amount,
+        }
+        .into(),
+    ))
+}
 
-    use std::io::Read;
+// Every pot of the saver, the default pot first
+fn load_pots(storage: &dyn Storage, saver: &Addr) -> StdResult<Vec<Pot>> {
+    let mut pots = POTS
+        .prefix(saver)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, pot)| pot))
+        .collect::<StdResult<Vec<_>>>()?;
+    match pots.iter().position(|pot| pot.name == DEFAULT_POT) {
+        Some(i) => {
+            let default_pot = pots.remove(i);
+            pots.insert(0, default_pot);
+        }
+        None => pots.insert(0, Pot::new(DEFAULT_POT)),
+    }
+    Ok(pots)
+}
 
-    use crate::state::{config, config_read};
+fn save_pots(storage: &mut dyn Storage, saver: &Addr, pots: &[Pot]) -> StdResult<()> {
+    for pot in pots {
+        POTS.save(storage, (saver, pot.name.as_str()), pot)?;
+    }
+    Ok(())
+}
 
-    use super::*;
-    use cosmwasm_std::{
-        coin,
-        testing::{mock_dependencies, mock_env, mock_info},
-        Addr, CosmosMsg, Storage, SubMsg,
-    };
+fn find_pot(pots: &[Pot], name: &str) -> Result<usize, ContractError> {
+    pots.iter()
+        .position(|pot| pot.name == name)
+        .ok_or_else(|| ContractError::PotNotFound {
+            name: name.to_string(),
+        })
+}
 
-    #[test]
-    fn try_instantiate() {
-        let mut deps = mock_dependencies();
-        let info = mock_info("anyone", &coins(2, "BTC"));
+// Add saved funds to the account and spread them over its pots,
+// returns the overflow that has to go back to the saver
+fn credit_savings(
+    storage: &mut dyn Storage,
+    saver: &Addr,
+    account: &mut Account,
+    saved: &Coin,
+) -> Result<Uint128, ContractError> {
+    let mut pots = load_pots(storage, saver)?;
+    let overflow = allocate(&account.allocation, &mut pots, saved)?;
+    account.credit(&Coin {
+        denom: saved.denom.clone(),
+        amount: saved.amount.checked_sub(overflow)?,
+    })?;
+    save_pots(storage, saver, &pots)?;
+    Ok(overflow)
+}
 
-        let msg = InstantiateMsg { savings_rate: 15 };
-        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        assert_eq!(("action", "instantiate"), res.attributes[0]);
+// Yield skips the allocations and lands in the default pot
+fn credit_default_pot(
+    storage: &mut dyn Storage,
+    saver: &Addr,
+    account: &mut Account,
+    amount: &Coin,
+) -> Result<(), ContractError> {
+    let mut pots = load_pots(storage, saver)?;
+    let default = find_pot(&pots, DEFAULT_POT)?;
+    pots[default].credit(amount)?;
+    save_pots(storage, saver, &pots)?;
+    account.credit(amount)?;
+    Ok(())
+}
 
-        let state = STATE.load(&deps.storage);
+// Credit the pots with their share of saved, returns what none of them took
+fn allocate(
+    mode: &AllocationMode,
+    pots: &mut [Pot],
+    saved: &Coin,
+) -> Result<Uint128, ContractError> {
+    let mut shares = vec![Uint128::zero(); pots.len()];
+    let mut remaining = saved.amount;
+    let mut overflow = Uint128::zero();
+    match mode {
+        AllocationMode::Fixed => {
+            for (pot, share) in pots.iter().zip(shares.iter_mut()).skip(1) {
+                let amount = saved
+                    .amount
+                    .checked_mul(Uint128::from(pot.allocation))?
+                    .u128()
+                    / BASIS_POINTS;
+                *share = Uint128::new(amount);
+                remaining = remaining.checked_sub(*share)?;
+            }
+        }
+        AllocationMode::Waterfall {
+            order,
+            overflow: overflow_to,
+        } => {
+            for name in order {
+                let i = find_pot(pots, name)?;
+                // a pot without a target takes everything that reaches it
+                let room = match &pots[i].target {
+                    None => remaining,
+                    Some(target) if target.denom != saved.denom => Uint128::zero(),
+                    Some(target) => {
+                        let balance = pots[i].balance_of(&saved.denom);
+                        if target.amount > balance {
+                            target.amount.checked_sub(balance)?
+                        } else {
+                            Uint128::zero()
+                        }
+                    }
+                };
+                let amount = room.min(remaining);
+                shares[i] = shares[i].checked_add(amount)?;
+                remaining = remaining.checked_sub(amount)?;
+            }
+            if *overflow_to == Overflow::Owner {
+                overflow = remaining;
+                remaining = Uint128::zero();
+            }
+        }
+    }
+    // the default pot takes the rest, rounding dust included
+    shares[0] = shares[0].checked_add(remaining)?;
 
-        assert_eq!(
-            state,
-            Ok(State {
-                owner: Addr::unchecked(MAIN_ADDRESS),
-                amount_received: coins(2, "BTC"),
-                savings_rate: 15,
-            })
-        );
+    for (pot, amount) in pots.iter_mut().zip(shares) {
+        pot.credit(&Coin {
+            denom: saved.denom.clone(),
+            amount,
+        })?;
     }
+    Ok(overflow)
+}
 
-    #[test]
-    fn try_transfer() {
-        let mut deps = mock_dependencies();
-        let info = mock_info("anyone", &[]);
+// Take funds out of the account, drawing on the default pot first
+fn debit_savings(
+    storage: &mut dyn Storage,
+    saver: &Addr,
+    account: &mut Account,
+    amount: &Coin,
+) -> Result<(), ContractError> {
+    account.debit(amount)?;
+    let mut pots = load_pots(storage, saver)?;
+    let mut remaining = amount.amount;
+    for pot in pots.iter_mut() {
+        let taken = pot.balance_of(&amount.denom).min(remaining);
+        pot.debit(&Coin {
+            denom: amount.denom.clone(),
+            amount: taken,
+        })?;
+        remaining = remaining.checked_sub(taken)?;
+    }
+    save_pots(storage, saver, &pots)?;
+    Ok(())
+}
 
-        instantiate(
-            deps.as_mut(),
-            mock_env(),
-            info,
-            InstantiateMsg { savings_rate: 15 },
-        )
-        .unwrap();
+// Store the record under the next sequence id
+fn record_deposit(
+    storage: &mut dyn Storage,
+    saver: &Addr,
+    record: DepositRecord,
+) -> StdResult<u64> {
+    let id = DEPOSIT_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    DEPOSIT_SEQ.save(storage, &id)?;
+    DEPOSITS.save(storage, (saver, id), &DepositRecord { id, ..record })?;
+    Ok(id)
+}
 
-        // only owner can transfer
-        let info = mock_info("anyone", &coins(1, "BTC"));
-        let msg = ExecuteMsg::Transfer {
-            received_funds: info.funds[0].clone(),
-            savings_rate: 15,
-        };
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-        assert_eq!(err, ContractError::Unauthorized {});
-        // can't receive empty funds
-        let info = mock_info(&MAIN_ADDRESS, &coins(0, "BTC"));
-        let msg = ExecuteMsg::Transfer {
-            received_funds: info.funds[0].clone(),
-            savings_rate: 15,
-        };
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-        assert_eq!(err, ContractError::EmptyTransfer {});
+// Apply a change to the saver's running totals of denom
+fn update_stats<F>(storage: &mut dyn Storage, saver: &Addr, denom: &str, action: F) -> StdResult<()>
+where
+    F: FnOnce(&mut DenomStats) -> StdResult<()>,
+{
+    let mut stats = STATS
+        .may_load(storage, (saver, denom))?
+        .unwrap_or_else(|| DenomStats::new(denom));
+    action(&mut stats)?;
+    STATS.save(storage, (saver, denom), &stats)
+}
 
-        // savings must be above 0 and less than 100
-        let info = mock_info(&MAIN_ADDRESS, &coins(2, "BTC"));
-        let msg = ExecuteMsg::Transfer {
-            received_funds: info.funds[0].clone(),
-            savings_rate: 101,
-        };
+fn validate_price(price: Decimal) -> Result<(), ContractError> {
+    if price.is_zero() {
+        return Err(ContractError::InvalidPrice {});
+    }
+    Ok(())
+}
 
-        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
-        assert_eq!(err, ContractError::InvalidSavingsRate {});
+fn validate_slippage(max_slippage: Decimal) -> Result<(), ContractError> {
+    if max_slippage > Decimal::one() {
+        return Err(ContractError::InvalidSlippage {});
+    }
+    Ok(())
+}
 
-        // works
-        let info = mock_info(&MAIN_ADDRESS, &coins(8500, "UST"));
-        let msg = ExecuteMsg::Transfer {
-            received_funds: info.funds[0].clone(),
-            savings_rate: 15,
-        };
+fn validate_savings_rate(savings_rate: u16) -> Result<(), ContractError> {
+    if u128::from(savings_rate) > BASIS_POINTS || savings_rate == 0 {
+        return Err(ContractError::InvalidSavingsRate {});
+    }
+    Ok(())
+}
 
-        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-        assert_eq!(1, res.messages.len());
-        assert_eq!(
-            res.messages[0],
-            SubMsg::new(BankMsg::Send {
-                to_address: MAIN_ADDRESS.to_string(),
-                amount: coins(7225, "UST"),
-            }),
-        );
+// Portion of amount paid back to the owner, the rounding remainder stays in savings
+fn payout_amount(
+    amount: Uint128,
+    savings_rate: u16,
+    rounding: &RoundingMode,
+) -> Result<Uint128, ContractError> {
+    let numerator = amount
+        .checked_mul(Uint128::from(BASIS_POINTS - u128::from(savings_rate)))?
+        .u128();
+    let quotient = numerator / BASIS_POINTS;
+    let remainder = numerator % BASIS_POINTS;
+    let round_up = match rounding {
+        RoundingMode::Floor => false,
+        RoundingMode::Ceil => remainder > 0,
+        RoundingMode::Bankers => {
+            remainder * 2 > BASIS_POINTS || (remainder * 2 == BASIS_POINTS && quotient % 2 == 1)
+        }
+    };
+
+    let payout = Uint128::new(quotient);
+    if round_up {
+        Ok(payout.checked_add(Uint128::new(1))?)
+    } else {
+        Ok(payout)
     }
+}
 
-    #[test]
-    fn try_flush() {
-        let mut deps = mock_dependencies();
-        let info = mock_info("anyone", &coins(1000, "ATOM"));
+// Compare two lists of coins regardless of order, ignoring empty coins
+fn funds_match(expected: &[Coin], received: &[Coin]) -> bool {
+    let normalize = |funds: &[Coin]| {
+        let mut funds: Vec<Coin> = funds
+            .iter()
+            .filter(|fund| !fund.amount.is_zero())
+            .cloned()
+            .collect();
+        funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+        funds
+    };
+    normalize(expected) == normalize(received)
+}
 
-        instantiate(
-            deps.as_mut(),
-            mock_env(),
-            info.clone(),
-            InstantiateMsg { savings_rate: 15 },
-        )
-        .unwrap();
+pub fn execute_flush(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // only account holders can flush, and only their own savings
+    let mut account = load_account(deps.storage, &info.sender)?;
+    // a withdrawal delay has to be sat out through a request
+    let delay = account.withdrawal_delay_at(env.block.time);
+    if delay > 0 {
+        return Err(ContractError::WithdrawalDelayed { delay });
+    }
+    check_unlocked(&account, &env)?;
 
-        // only owner can flush
-        let info = mock_info("anyone", &[]);
+    // large withdrawals wait for the approvers
+    if let Some(approvers) = &account.approvers {
+        if account.balance.is_empty() {
+            return Err(ContractError::EmptyBalance {});
+        }
+        if needs_approval(
+            approvers,
+            &account.recent_withdrawals,
+            env.block.time,
+            &account.balance,
+        ) {
+            return propose(
+                deps.storage,
+                &env,
+                &info.sender,
+                approvers,
+                ProposalKind::Flush {},
+                account.balance.clone(),
+            );
+        }
+    }
 
-        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Flush {}).unwrap_err();
-        assert_eq!(err, ContractError::Unauthorized {});
+    let balance = withdraw_all(deps.storage, env.block.time, &info.sender, &mut account)?;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
 
-        // can't flush an empty balance, set empty balance before instantiation
-        let env = mock_env();
-        let info = mock_info(&MAIN_ADDRESS, &[]);
+    Ok(Response::new()
+        .add_messages(send_msgs(&info.sender, balance)?)
+        .add_attribute("action", "flush"))
+}
 
-        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Flush {}).unwrap_err();
-        assert_eq!(err, ContractError::EmptyBalance {});
+// Open a proposal paying out amount once the approvers agree
+fn propose(
+    storage: &mut dyn Storage,
+    env: &Env,
+    owner: &Addr,
+    approvers: &ApproverSet,
+    kind: ProposalKind,
+    amount: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let id = PROPOSAL_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    PROPOSAL_SEQ.save(storage, &id)?;
+    let proposal = Proposal {
+        id,
+        owner: owner.clone(),
+        kind,
+        amount,
+        approvals: vec![],
+        expires_at: env.block.time.plus_seconds(approvers.expires_in),
+    };
+    PROPOSALS.save(storage, id, &proposal)?;
+    // a new inheritance claim replaces the previous one
+    if let ProposalKind::Inheritance { .. } = proposal.kind {
+        if let Some(previous) = INHERITANCE_PROPOSALS.may_load(storage, owner)? {
+            PROPOSALS.remove(storage, previous);
+        }
+        INHERITANCE_PROPOSALS.save(storage, owner, &id)?;
+    }
+    Ok(Response::new()
+        .add_attribute("action", "propose_withdrawal")
+        .add_attribute("proposal_id", id.to_string())
+        .add_attribute("expires_at", proposal.expires_at.to_string()))
+}
 
-        // works
-        let env = mock_env();
-        let info = mock_info(&MAIN_ADDRESS, &[]);
-        deps.querier
-            .update_balance(&env.contract.address, coins(2000, "ETH"));
-        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Flush {}).unwrap();
-        assert_eq!(1, res.messages.len());
+// Any denom going over its threshold together with what was withdrawn within
+// APPROVAL_WINDOW, or without a threshold, needs approvals
+fn needs_approval(
+    approvers: &ApproverSet,
+    recent: &[RecentWithdrawal],
+    now: Timestamp,
+    amount: &[Coin],
+) -> bool {
+    amount.iter().any(|coin| {
+        let withdrawn: Uint128 = recent
+            .iter()
+            .filter(|recent| {
+                recent.amount.denom == coin.denom && recent.at.plus_seconds(APPROVAL_WINDOW) > now
+            })
+            .map(|recent| recent.amount.amount)
+            .sum();
+        approvers
+            .threshold
+            .iter()
+            .find(|limit| limit.denom == coin.denom)
+            .is_none_or(|limit| coin.amount.saturating_add(withdrawn) > limit.amount)
+    })
+}
+
+// Count a withdrawal against the approvers' threshold for APPROVAL_WINDOW
+fn record_withdrawal(account: &mut Account, now: Timestamp, withdrawn: &Coin) {
+    if account.approvers.is_none() {
+        return;
+    }
+    account
+        .recent_withdrawals
+        .retain(|recent| recent.at.plus_seconds(APPROVAL_WINDOW) > now);
+    account.recent_withdrawals.push(RecentWithdrawal {
+        amount: withdrawn.clone(),
+        at: now,
+    });
+}
+
+// Set the first approvers right away, any later change waits out APPROVERS_CHANGE_DELAY
+pub fn execute_update_approvers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    approvers: Option<ApproverSetMsg>,
+) -> Result<Response, ContractError> {
+    // savers update their own account
+    let mut account = load_account(deps.storage, &info.sender)?;
+    let approvers = approvers
+        .map(|set| validate_approvers(deps.api, set))
+        .transpose()?;
+
+    let res = if account.approvers.is_none() {
+        account.approvers = approvers;
+        account.pending_approvers = None;
+        Response::new().add_attribute("action", "update_approvers")
+    } else {
+        let change = ApproverChange {
+            approvers,
+            effective_at: env.block.time.plus_seconds(APPROVERS_CHANGE_DELAY),
+        };
+        let res = Response::new()
+            .add_attribute("action", "propose_approvers")
+            .add_attribute("effective_at", change.effective_at.to_string());
+        account.pending_approvers = Some(change);
+        res
+    };
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(res)
+}
+
+pub fn execute_confirm_approvers(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    let change = account
+        .pending_approvers
+        .take()
+        .ok_or(ContractError::NoPendingApprovers {})?;
+    if env.block.time < change.effective_at {
+        return Err(ContractError::ApproversChangeNotReady {
+            effective_at: change.effective_at,
+        });
+    }
+    account.approvers = change.approvers;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new().add_attribute("action", "confirm_approvers"))
+}
+
+fn validate_approvers(api: &dyn Api, set: ApproverSetMsg) -> Result<ApproverSet, ContractError> {
+    let mut approvers: Vec<Addr> = vec![];
+    for approver in set.approvers {
+        let approver = api.addr_validate(&approver)?;
+        if approvers.contains(&approver) {
+            return Err(ContractError::InvalidApprovers {
+                reason: format!("{} is listed twice", approver),
+            });
+        }
+        approvers.push(approver);
+    }
+    if set.required == 0 || set.required as usize > approvers.len() {
+        return Err(ContractError::InvalidApprovers {
+            reason: format!(
+                "{} of {} approvals can't be met",
+                set.required,
+                approvers.len()
+            ),
+        });
+    }
+    if set.expires_in == 0 {
+        return Err(ContractError::InvalidApprovers {
+            reason: "proposals expire immediately".to_string(),
+        });
+    }
+    validate_period(set.expires_in)?;
+    Ok(ApproverSet {
+        approvers,
+        required: set.required,
+        threshold: set.threshold,
+        expires_in: set.expires_in,
+    })
+}
+
+// Load a proposal the sender can vote on
+fn load_open_proposal(
+    storage: &dyn Storage,
+    env: &Env,
+    sender: &Addr,
+    id: u64,
+) -> Result<(Proposal, Account), ContractError> {
+    let proposal = PROPOSALS
+        .may_load(storage, id)?
+        .ok_or(ContractError::ProposalNotFound { id })?;
+    let account = load_account(storage, &proposal.owner)?;
+    let is_approver = account
+        .approvers
+        .as_ref()
+        .is_some_and(|set| set.approvers.contains(sender));
+    if !is_approver {
+        return Err(ContractError::Unauthorized {});
+    }
+    if env.block.time >= proposal.expires_at {
+        return Err(ContractError::ProposalExpired { id });
+    }
+    Ok((proposal, account))
+}
+
+pub fn execute_approve_proposal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let (mut proposal, mut account) = load_open_proposal(deps.storage, &env, &info.sender, id)?;
+    if proposal.approvals.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved {});
+    }
+    proposal.approvals.push(info.sender.clone());
+
+    let required = account.approvers.as_ref().map_or(0, |set| set.required);
+    let mut res = Response::new()
+        .add_attribute("action", "approve_proposal")
+        .add_attribute("proposal_id", id.to_string())
+        .add_attribute("approvals", proposal.approvals.len().to_string());
+    if (proposal.approvals.len() as u32) < required {
+        PROPOSALS.save(deps.storage, id, &proposal)?;
+        return Ok(res);
+    }
+
+    // the last approval pays out, only early withdrawals may break the lock
+    if proposal.kind != (ProposalKind::EarlyWithdraw {}) {
+        check_unlocked(&account, &env)?;
+    }
+    if let ProposalKind::Withdrawal { id } = proposal.kind {
+        // the owner may have cancelled the request in the meantime
+        WITHDRAWALS
+            .may_load(deps.storage, (&proposal.owner, id))?
+            .ok_or(ContractError::WithdrawalNotFound { id })?;
+        WITHDRAWALS.remove(deps.storage, (&proposal.owner, id));
+    }
+    if let ProposalKind::Inheritance { beneficiary } = &proposal.kind {
+        // the saver may have changed the inheritance since the claim
+        claimable_inheritance(&account, beneficiary, env.block.time)?;
+        INHERITANCE_PROPOSALS.remove(deps.storage, &proposal.owner);
+    }
+    for withdrawn in proposal.amount.iter() {
+        if account.balance_of(&withdrawn.denom) < withdrawn.amount {
+            return Err(ContractError::InsufficientFunds {});
+        }
+        withdraw(
+            deps.storage,
+            env.block.time,
+            &proposal.owner,
+            &mut account,
+            withdrawn,
+        )?;
+    }
+    PROPOSALS.remove(deps.storage, id);
+
+    let msgs = match proposal.kind {
+        ProposalKind::EarlyWithdraw {} => {
+            let (msgs, withheld) =
+                penalize(deps.storage, &proposal.owner, &account, proposal.amount)?;
+            res = res.add_attribute("penalty", format_coins(&withheld));
+            msgs
+        }
+        ProposalKind::Inheritance { beneficiary } => send_msgs(&beneficiary, proposal.amount)?,
+        ProposalKind::Flush {} | ProposalKind::Withdrawal { .. } => {
+            send_msgs(&proposal.owner, proposal.amount)?
+        }
+    };
+    ACCOUNTS.save(deps.storage, &proposal.owner, &account)?;
+
+    Ok(res.add_messages(msgs))
+}
+
+pub fn execute_revoke_approval(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let (mut proposal, _) = load_open_proposal(deps.storage, &env, &info.sender, id)?;
+    if !proposal.approvals.contains(&info.sender) {
+        return Err(ContractError::NotApproved {});
+    }
+    proposal
+        .approvals
+        .retain(|approver| *approver != info.sender);
+    PROPOSALS.save(deps.storage, id, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_approval")
+        .add_attribute("proposal_id", id.to_string()))
+}
+
+// Empty the account, pending requests included
+fn withdraw_all(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    saver: &Addr,
+    account: &mut Account,
+) -> Result<Vec<Coin>, ContractError> {
+    let balance = account.balance.clone();
+    // can't withdraw an empty balance
+    if balance.is_empty() {
+        return Err(ContractError::EmptyBalance {});
+    }
+    for request in load_withdrawals(storage, saver)? {
+        WITHDRAWALS.remove(storage, (saver, request.id));
+    }
+    for withdrawn in balance.iter() {
+        withdraw(storage, now, saver, account, withdrawn)?;
+    }
+    Ok(balance)
+}
+
+// Take withdrawn out of the savings and count it in the stats
+fn withdraw(
+    storage: &mut dyn Storage,
+    now: Timestamp,
+    saver: &Addr,
+    account: &mut Account,
+    withdrawn: &Coin,
+) -> Result<(), ContractError> {
+    // staked savings have to come back from the validator first
+    if account.liquid_of(&withdrawn.denom, now) < withdrawn.amount {
+        return Err(ContractError::SavingsStaked {});
+    }
+    debit_savings(storage, saver, account, withdrawn)?;
+    record_withdrawal(account, now, withdrawn);
+    update_stats(storage, saver, &withdrawn.denom, |stats| {
+        stats.total_withdrawn = stats.total_withdrawn.checked_add(withdrawn.amount)?;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+// Locked savings stay in the contract
+fn check_unlocked(account: &Account, env: &Env) -> Result<(), ContractError> {
+    if let Some(lock) = account.lock.clone() {
+        if lock.is_locked(&env.block) {
+            return Err(ContractError::StillLocked { unlocks_at: lock });
+        }
+    }
+    Ok(())
+}
+
+fn load_withdrawals(storage: &dyn Storage, saver: &Addr) -> StdResult<Vec<WithdrawalRequest>> {
+    WITHDRAWALS
+        .prefix(saver)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, request)| request))
+        .collect()
+}
+
+pub fn execute_request_withdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    denom: String,
+) -> Result<Response, ContractError> {
+    let account = load_account(deps.storage, &info.sender)?;
+    if amount.is_zero() {
+        return Err(ContractError::EmptyWithdrawal {});
+    }
+    // pending requests hold on to their funds
+    let mut available = account.balance_of(&denom);
+    for request in load_withdrawals(deps.storage, &info.sender)? {
+        if request.amount.denom == denom {
+            available = available.checked_sub(request.amount.amount)?;
+        }
+    }
+    if amount > available {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    let id = WITHDRAWAL_SEQ.may_load(deps.storage)?.unwrap_or_default() + 1;
+    WITHDRAWAL_SEQ.save(deps.storage, &id)?;
+    let request = WithdrawalRequest {
+        id,
+        amount: Coin { denom, amount },
+        requested_at: env.block.time,
+        available_at: env
+            .block
+            .time
+            .plus_seconds(account.withdrawal_delay_at(env.block.time)),
+    };
+    WITHDRAWALS.save(deps.storage, (&info.sender, id), &request)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "request_withdrawal")
+        .add_attribute("id", id.to_string())
+        .add_attribute("available_at", request.available_at.to_string()))
+}
+
+pub fn execute_execute_withdrawal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    let request = WITHDRAWALS
+        .may_load(deps.storage, (&info.sender, id))?
+        .ok_or(ContractError::WithdrawalNotFound { id })?;
+    if env.block.time < request.available_at {
+        return Err(ContractError::WithdrawalNotReady {
+            available_at: request.available_at,
+        });
+    }
+    check_unlocked(&account, &env)?;
+    // a released goal may have taken the funds in the meantime
+    if account.balance_of(&request.amount.denom) < request.amount.amount {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    // large withdrawals wait for the approvers
+    if let Some(approvers) = &account.approvers {
+        if needs_approval(
+            approvers,
+            &account.recent_withdrawals,
+            env.block.time,
+            std::slice::from_ref(&request.amount),
+        ) {
+            return propose(
+                deps.storage,
+                &env,
+                &info.sender,
+                approvers,
+                ProposalKind::Withdrawal { id },
+                vec![request.amount],
+            );
+        }
+    }
+
+    let withdrawn = request.amount;
+    withdraw(
+        deps.storage,
+        env.block.time,
+        &info.sender,
+        &mut account,
+        &withdrawn,
+    )?;
+    WITHDRAWALS.remove(deps.storage, (&info.sender, id));
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new()
+        .add_messages(send_msgs(&info.sender, vec![withdrawn])?)
+        .add_attribute("action", "execute_withdrawal")
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn execute_cancel_withdrawal(
+    deps: DepsMut,
+    info: MessageInfo,
+    id: u64,
+) -> Result<Response, ContractError> {
+    load_account(deps.storage, &info.sender)?;
+    if WITHDRAWALS
+        .may_load(deps.storage, (&info.sender, id))?
+        .is_none()
+    {
+        return Err(ContractError::WithdrawalNotFound { id });
+    }
+    WITHDRAWALS.remove(deps.storage, (&info.sender, id));
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_withdrawal")
+        .add_attribute("id", id.to_string()))
+}
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    savings_rate: Option<u16>,
+    rounding: Option<RoundingMode>,
+    withdrawal_delay: Option<u64>,
+) -> Result<Response, ContractError> {
+    // savers update their own account
+    let mut account = load_account(deps.storage, &info.sender)?;
+
+    if let Some(savings_rate) = savings_rate {
+        validate_savings_rate(savings_rate)?;
+        account.savings_rate = savings_rate;
+    }
+    if let Some(rounding) = rounding {
+        account.rounding = rounding;
+    }
+    // requests already made keep the delay they were made with, and a
+    // shorter delay only applies once the current one has been sat out
+    if let Some(withdrawal_delay) = withdrawal_delay {
+        validate_period(withdrawal_delay)?;
+        let current = account.withdrawal_delay_at(env.block.time);
+        if withdrawal_delay < current {
+            account.withdrawal_delay = current;
+            account.pending_withdrawal_delay = Some(DelayChange {
+                delay: withdrawal_delay,
+                effective_at: env.block.time.plus_seconds(current),
+            });
+        } else {
+            account.withdrawal_delay = withdrawal_delay;
+            account.pending_withdrawal_delay = None;
+        }
+    }
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("rate", account.savings_rate.to_string()))
+}
+
+pub fn execute_update_split(
+    deps: DepsMut,
+    info: MessageInfo,
+    split: Option<SpendingSplitMsg>,
+) -> Result<Response, ContractError> {
+    // savers update their own account
+    let mut account = load_account(deps.storage, &info.sender)?;
+    account.split = split
+        .map(|split| validate_split(deps.api, split))
+        .transpose()?;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new().add_attribute("action", "update_split"))
+}
+
+pub fn execute_early_withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // breaking the lock costs a penalty, a guardian's approval, or both
+    let account = load_account(deps.storage, &info.sender)?;
+    if account.penalty.is_none() && account.guardian.is_none() {
+        return Err(ContractError::EarlyWithdrawDisabled {});
+    }
+    let locked = account
+        .lock
+        .as_ref()
+        .is_some_and(|lock| lock.is_locked(&env.block));
+    // with no lock to break, the withdrawal delay applies as it does to a flush
+    if !locked {
+        let delay = account.withdrawal_delay_at(env.block.time);
+        if delay > 0 {
+            return Err(ContractError::WithdrawalDelayed { delay });
+        }
+    }
+    if let Some(guardian) = &account.guardian {
+        if locked {
+            if GUARDIAN_REQUESTS
+                .may_load(deps.storage, &info.sender)?
+                .is_some()
+            {
+                return Err(ContractError::GuardianRequestPending {});
+            }
+            GUARDIAN_REQUESTS.save(
+                deps.storage,
+                &info.sender,
+                &GuardianRequest {
+                    requested_at: env.block.time,
+                },
+            )?;
+            return Ok(Response::new()
+                .add_attribute("action", "request_guardian_approval")
+                .add_attribute("guardian", guardian.to_string()));
+        }
+    }
+    early_withdraw(deps.storage, &env, &info.sender, account)
+}
+
+// Pay out all the savings, withholding the penalty if one is set.
+// Large balances wait for the approvers instead
+fn early_withdraw(
+    storage: &mut dyn Storage,
+    env: &Env,
+    saver: &Addr,
+    mut account: Account,
+) -> Result<Response, ContractError> {
+    if let Some(approvers) = &account.approvers {
+        if needs_approval(
+            approvers,
+            &account.recent_withdrawals,
+            env.block.time,
+            &account.balance,
+        ) {
+            return propose(
+                storage,
+                env,
+                saver,
+                approvers,
+                ProposalKind::EarlyWithdraw {},
+                account.balance.clone(),
+            );
+        }
+    }
+    let balance = withdraw_all(storage, env.block.time, saver, &mut account)?;
+    let (msgs, withheld) = penalize(storage, saver, &account, balance)?;
+    ACCOUNTS.save(storage, saver, &account)?;
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "early_withdraw")
+        .add_attribute("penalty", format_coins(&withheld)))
+}
+
+// Split withdrawn savings into the saver's payout and the withheld penalty
+fn penalize(
+    storage: &mut dyn Storage,
+    saver: &Addr,
+    account: &Account,
+    balance: Vec<Coin>,
+) -> Result<(Vec<CosmosMsg>, Vec<Coin>), ContractError> {
+    let rate = account.penalty.as_ref().map_or(0, |penalty| penalty.rate);
+    let mut payout = vec![];
+    let mut withheld = vec![];
+    for withdrawn in balance {
+        // rounded up, any rate withholds something
+        let amount = Uint128::new(
+            withdrawn
+                .amount
+                .checked_mul(Uint128::from(rate))?
+                .u128()
+                .div_ceil(BASIS_POINTS),
+        );
+        update_stats(storage, saver, &withdrawn.denom, |stats| {
+            stats.total_penalty = stats.total_penalty.checked_add(amount)?;
+            Ok(())
+        })?;
+        if !amount.is_zero() {
+            withheld.push(Coin {
+                denom: withdrawn.denom.clone(),
+                amount,
+            });
+        }
+        let paid = withdrawn.amount.checked_sub(amount)?;
+        if !paid.is_zero() {
+            payout.push(Coin {
+                denom: withdrawn.denom,
+                amount: paid,
+            });
+        }
+    }
+
+    let mut msgs = vec![];
+    if !payout.is_empty() {
+        msgs.extend(send_msgs(saver, payout)?);
+    }
+    if !withheld.is_empty() {
+        msgs.extend(
+            match account
+                .penalty
+                .as_ref()
+                .and_then(|penalty| penalty.recipient.as_ref())
+            {
+                Some(recipient) => send_msgs(recipient, withheld.clone())?,
+                None => burn_msgs(withheld.clone())?,
+            },
+        );
+    }
+    Ok((msgs, withheld))
+}
+
+fn format_coins(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// Called by the guardian, pays out the pending early withdrawal
+pub fn execute_approve(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let saver = deps.api.addr_validate(&owner)?;
+    let account = load_account(deps.storage, &saver)?;
+    if account.guardian.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if GUARDIAN_REQUESTS.may_load(deps.storage, &saver)?.is_none() {
+        return Err(ContractError::NoGuardianRequest {});
+    }
+    GUARDIAN_REQUESTS.remove(deps.storage, &saver);
+
+    Ok(early_withdraw(deps.storage, &env, &saver, account)?
+        .add_attribute("approved_by", info.sender))
+}
+
+pub fn execute_reject(
+    deps: DepsMut,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let saver = deps.api.addr_validate(&owner)?;
+    let account = load_account(deps.storage, &saver)?;
+    if account.guardian.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if GUARDIAN_REQUESTS.may_load(deps.storage, &saver)?.is_none() {
+        return Err(ContractError::NoGuardianRequest {});
+    }
+    GUARDIAN_REQUESTS.remove(deps.storage, &saver);
+
+    Ok(Response::new()
+        .add_attribute("action", "reject")
+        .add_attribute("owner", saver))
+}
+
+// Set the first guardian right away, any later change waits out GUARDIAN_CHANGE_DELAY
+pub fn execute_propose_guardian(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    let guardian = guardian
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
+    if guardian.as_ref() == Some(&info.sender) {
+        return Err(ContractError::InvalidGuardian {});
+    }
+
+    let res = if account.guardian.is_none() {
+        account.guardian = guardian;
+        account.pending_guardian = None;
+        Response::new().add_attribute("action", "set_guardian")
+    } else {
+        let change = GuardianChange {
+            guardian,
+            effective_at: env.block.time.plus_seconds(GUARDIAN_CHANGE_DELAY),
+        };
+        let res = Response::new()
+            .add_attribute("action", "propose_guardian")
+            .add_attribute("effective_at", change.effective_at.to_string());
+        account.pending_guardian = Some(change);
+        res
+    };
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(res)
+}
+
+pub fn execute_confirm_guardian(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    let change = account
+        .pending_guardian
+        .take()
+        .ok_or(ContractError::NoPendingGuardian {})?;
+    if env.block.time < change.effective_at {
+        return Err(ContractError::GuardianChangeNotReady {
+            effective_at: change.effective_at,
+        });
+    }
+    account.guardian = change.guardian;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new().add_attribute("action", "confirm_guardian"))
+}
+
+fn validate_penalty(
+    api: &dyn Api,
+    saver: &Addr,
+    penalty: PenaltyMsg,
+) -> Result<Penalty, ContractError> {
+    if penalty.rate == 0 || u128::from(penalty.rate) > BASIS_POINTS {
+        return Err(ContractError::InvalidPenalty {});
+    }
+    let recipient = penalty
+        .recipient
+        .map(|recipient| api.addr_validate(&recipient))
+        .transpose()?;
+    // a penalty paid back to the saver costs nothing
+    if recipient.as_ref() == Some(saver) {
+        return Err(ContractError::InvalidPenalty {});
+    }
+    Ok(Penalty {
+        rate: penalty.rate,
+        recipient,
+    })
+}
+
+pub fn execute_update_penalty(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    penalty: Option<PenaltyMsg>,
+) -> Result<Response, ContractError> {
+    // savers update their own account
+    let mut account = load_account(deps.storage, &info.sender)?;
+    let penalty = penalty
+        .map(|penalty| validate_penalty(deps.api, &info.sender, penalty))
+        .transpose()?;
+    // a locked saver can't open a way out or make it cheaper, only raise the rate
+    if let Some(lock) = account.lock.clone() {
+        if lock.is_locked(&env.block) {
+            let raised = match (&account.penalty, &penalty) {
+                (Some(current), Some(new)) => {
+                    new.rate >= current.rate && new.recipient == current.recipient
+                }
+                _ => account.penalty == penalty,
+            };
+            if !raised {
+                return Err(ContractError::StillLocked { unlocks_at: lock });
+            }
+        }
+    }
+    account.penalty = penalty;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new().add_attribute("action", "update_penalty"))
+}
+
+pub fn execute_update_inheritance(
+    deps: DepsMut,
+    info: MessageInfo,
+    inheritance: Option<InheritanceMsg>,
+) -> Result<Response, ContractError> {
+    // savers update their own account
+    let mut account = load_account(deps.storage, &info.sender)?;
+    account.inheritance = inheritance
+        .map(|inheritance| -> Result<_, ContractError> {
+            let beneficiary = deps.api.addr_validate(&inheritance.beneficiary)?;
+            if beneficiary == info.sender {
+                return Err(ContractError::InvalidInheritance {
+                    reason: "beneficiary is the saver".to_string(),
+                });
+            }
+            if inheritance.inactivity_period == 0 {
+                return Err(ContractError::InvalidInheritance {
+                    reason: "no inactivity period".to_string(),
+                });
+            }
+            validate_period(inheritance.inactivity_period)?;
+            Ok(Inheritance {
+                beneficiary,
+                inactivity_period: inheritance.inactivity_period,
+            })
+        })
+        .transpose()?;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new().add_attribute("action", "update_inheritance"))
+}
+
+pub fn execute_claim_inheritance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let saver = deps.api.addr_validate(&owner)?;
+    let mut account = load_account(deps.storage, &saver)?;
+    let inheritance = claimable_inheritance(&account, &info.sender, env.block.time)?;
+    check_unlocked(&account, &env)?;
+    // large inheritances wait for the approvers
+    if let Some(approvers) = &account.approvers {
+        if needs_approval(
+            approvers,
+            &account.recent_withdrawals,
+            env.block.time,
+            &account.balance,
+        ) {
+            return propose(
+                deps.storage,
+                &env,
+                &saver,
+                approvers,
+                ProposalKind::Inheritance {
+                    beneficiary: inheritance.beneficiary,
+                },
+                account.balance.clone(),
+            );
+        }
+    }
+
+    let balance = withdraw_all(deps.storage, env.block.time, &saver, &mut account)?;
+    ACCOUNTS.save(deps.storage, &saver, &account)?;
+
+    Ok(Response::new()
+        .add_messages(send_msgs(&inheritance.beneficiary, balance)?)
+        .add_attribute("action", "claim_inheritance")
+        .add_attribute("owner", saver))
+}
+
+// The inheritance the claimant may take once the saver has been quiet long enough
+fn claimable_inheritance(
+    account: &Account,
+    claimant: &Addr,
+    now: Timestamp,
+) -> Result<Inheritance, ContractError> {
+    // only the beneficiary can claim
+    let inheritance = match &account.inheritance {
+        Some(inheritance) if inheritance.beneficiary == *claimant => inheritance.clone(),
+        _ => return Err(ContractError::Unauthorized {}),
+    };
+    let claimable_at = account
+        .last_activity
+        .plus_seconds(inheritance.inactivity_period);
+    if now < claimable_at {
+        return Err(ContractError::InheritanceNotClaimable { claimable_at });
+    }
+    Ok(inheritance)
+}
+
+fn validate_pot_name(name: &str) -> Result<(), ContractError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_POT_NAME_LENGTH
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(ContractError::InvalidPotName {
+            name: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub fn execute_create_pot(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    load_account(deps.storage, &info.sender)?;
+    validate_pot_name(&name)?;
+    let pots = load_pots(deps.storage, &info.sender)?;
+    if pots.iter().any(|pot| pot.name == name) {
+        return Err(ContractError::PotExists { name });
+    }
+    POTS.save(
+        deps.storage,
+        (&info.sender, name.as_str()),
+        &Pot::new(&name),
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "create_pot")
+        .add_attribute("pot", name))
+}
+
+pub fn execute_rename_pot(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    new_name: String,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    if name == DEFAULT_POT {
+        return Err(ContractError::DefaultPot {});
+    }
+    validate_pot_name(&new_name)?;
+    let pots = load_pots(deps.storage, &info.sender)?;
+    let mut pot = pots[find_pot(&pots, &name)?].clone();
+    if pots.iter().any(|pot| pot.name == new_name) {
+        return Err(ContractError::PotExists { name: new_name });
+    }
+
+    POTS.remove(deps.storage, (&info.sender, name.as_str()));
+    pot.name = new_name.clone();
+    POTS.save(deps.storage, (&info.sender, new_name.as_str()), &pot)?;
+    // keep the pot's place in the waterfall
+    if let AllocationMode::Waterfall { order, .. } = &mut account.allocation {
+        for pot_name in order.iter_mut().filter(|pot_name| **pot_name == name) {
+            *pot_name = new_name.clone();
+        }
+        ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "rename_pot")
+        .add_attribute("pot", new_name))
+}
+
+pub fn execute_close_pot(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    if name == DEFAULT_POT {
+        return Err(ContractError::DefaultPot {});
+    }
+    let mut pots = load_pots(deps.storage, &info.sender)?;
+    let closed = pots.remove(find_pot(&pots, &name)?);
+
+    // savings never leave the account, they go back to the default pot
+    for saved in closed.balance.iter() {
+        pots[0].credit(saved)?;
+    }
+    POTS.remove(deps.storage, (&info.sender, name.as_str()));
+    POTS.save(deps.storage, (&info.sender, DEFAULT_POT), &pots[0])?;
+    if let AllocationMode::Waterfall { order, .. } = &mut account.allocation {
+        order.retain(|pot_name| *pot_name != name);
+        ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "close_pot")
+        .add_attribute("pot", name))
+}
+
+pub fn execute_move_pot_funds(
+    deps: DepsMut,
+    info: MessageInfo,
+    from: String,
+    to: String,
+    amount: Coin,
+) -> Result<Response, ContractError> {
+    load_account(deps.storage, &info.sender)?;
+    let mut pots = load_pots(deps.storage, &info.sender)?;
+    let from_index = find_pot(&pots, &from)?;
+    let to_index = find_pot(&pots, &to)?;
+    if pots[from_index].balance_of(&amount.denom) < amount.amount {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    pots[from_index].debit(&amount)?;
+    pots[to_index].credit(&amount)?;
+    save_pots(deps.storage, &info.sender, &pots)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "move_pot_funds")
+        .add_attribute("from", from)
+        .add_attribute("to", to)
+        .add_attribute("amount", amount.to_string()))
+}
+
+pub fn execute_set_allocations(
+    deps: DepsMut,
+    info: MessageInfo,
+    allocations: Vec<PotAllocation>,
+) -> Result<Response, ContractError> {
+    load_account(deps.storage, &info.sender)?;
+    let mut pots = load_pots(deps.storage, &info.sender)?;
+    for pot in pots.iter_mut() {
+        pot.allocation = 0;
+    }
+
+    let mut total: u128 = 0;
+    for allocation in allocations {
+        // the default pot always takes the remainder
+        if allocation.name == DEFAULT_POT {
+            return Err(ContractError::InvalidAllocation {});
+        }
+        let i = find_pot(&pots, &allocation.name)?;
+        if pots[i].allocation != 0 {
+            return Err(ContractError::InvalidAllocation {});
+        }
+        pots[i].allocation = allocation.allocation;
+        total += u128::from(allocation.allocation);
+    }
+    if total > BASIS_POINTS {
+        return Err(ContractError::InvalidAllocation {});
+    }
+    save_pots(deps.storage, &info.sender, &pots)?;
+
+    Ok(Response::new().add_attribute("action", "set_allocations"))
+}
+
+pub fn execute_set_pot_target(
+    deps: DepsMut,
+    info: MessageInfo,
+    name: String,
+    target: Option<Coin>,
+) -> Result<Response, ContractError> {
+    load_account(deps.storage, &info.sender)?;
+    if let Some(target) = &target {
+        if target.amount.is_zero() {
+            return Err(ContractError::InvalidGoal {});
+        }
+    }
+    let mut pots = load_pots(deps.storage, &info.sender)?;
+    let i = find_pot(&pots, &name)?;
+    pots[i].target = target;
+    POTS.save(deps.storage, (&info.sender, name.as_str()), &pots[i])?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_pot_target")
+        .add_attribute("pot", name))
+}
+
+pub fn execute_set_allocation_mode(
+    deps: DepsMut,
+    info: MessageInfo,
+    mode: AllocationMode,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    if let AllocationMode::Waterfall { order, .. } = &mode {
+        let pots = load_pots(deps.storage, &info.sender)?;
+        for (i, name) in order.iter().enumerate() {
+            find_pot(&pots, name)?;
+            // every pot fills once
+            if order[..i].contains(name) {
+                return Err(ContractError::InvalidAllocation {});
+            }
+        }
+    }
+    account.allocation = mode;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new().add_attribute("action", "set_allocation_mode"))
+}
+
+// Only the owner and the pause guardian can flip the switches
+fn check_can_pause(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    let state = STATE.load(storage)?;
+    if *sender != state.owner && state.pause_guardian.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn execute_pause(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    scope: Option<PauseScope>,
+) -> Result<Response, ContractError> {
+    check_can_pause(deps.storage, &info.sender)?;
+    let mut pause = PAUSE.may_load(deps.storage)?.unwrap_or_default();
+    let scope = scope.unwrap_or(PauseScope::All);
+    if scope != PauseScope::Withdrawals {
+        pause.deposits = true;
+    }
+    if scope != PauseScope::Deposits {
+        pause.withdrawals = true;
+    }
+    pause.paused_by = Some(info.sender.clone());
+    pause.paused_at = Some(env.block.time);
+    PAUSE.save(deps.storage, &pause)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "pause")
+        .add_attribute("paused_by", info.sender))
+}
+
+pub fn execute_unpause(
+    deps: DepsMut,
+    info: MessageInfo,
+    scope: Option<PauseScope>,
+) -> Result<Response, ContractError> {
+    check_can_pause(deps.storage, &info.sender)?;
+    let mut pause = PAUSE.may_load(deps.storage)?.unwrap_or_default();
+    let scope = scope.unwrap_or(PauseScope::All);
+    if scope != PauseScope::Withdrawals {
+        pause.deposits = false;
+    }
+    if scope != PauseScope::Deposits {
+        pause.withdrawals = false;
+    }
+    // who paused and when stays on record until everything resumes
+    if !pause.deposits && !pause.withdrawals {
+        PAUSE.remove(deps.storage);
+    } else {
+        PAUSE.save(deps.storage, &pause)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "unpause")
+        .add_attribute("unpaused_by", info.sender))
+}
+
+pub fn execute_set_pause_guardian(
+    deps: DepsMut,
+    info: MessageInfo,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    // only owner can choose the pause guardian
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    state.pause_guardian = guardian
+        .map(|guardian| deps.api.addr_validate(&guardian))
+        .transpose()?;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attribute("action", "set_pause_guardian"))
+}
+
+pub fn execute_update_staking(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    validator: Option<String>,
+    unbonding_period: u64,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    // only owner can choose the validator
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    validate_period(unbonding_period)?;
+    // a shorter period would let savings still unbonding count as liquid
+    if unbonding_period < state.unbonding_period
+        && !deps
+            .querier
+            .query_all_delegations(&env.contract.address)?
+            .is_empty()
+    {
+        return Err(ContractError::SavingsStaked {});
+    }
+    if let Some(validator) = &validator {
+        if deps.querier.query_validator(validator)?.is_none() {
+            return Err(ContractError::InvalidValidator {
+                validator: validator.clone(),
+            });
+        }
+    }
+    state.validator = validator;
+    state.unbonding_period = unbonding_period;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attribute("action", "update_staking"))
+}
+
+pub fn execute_unstake(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let saver = deps.api.addr_validate(&address)?;
+    let mut account = load_account(deps.storage, &saver)?;
+    let state = STATE.load(deps.storage)?;
+    // anyone can unstake once the unlock is close enough, the saver at any time
+    if info.sender != saver {
+        if let Some(due_at) = unstake_at(&account, state.unbonding_period) {
+            if env.block.time < due_at {
+                return Err(ContractError::UnstakeNotDue { due_at });
+            }
+        }
+    }
+    account
+        .unbonding
+        .retain(|unbonding| unbonding.completes_at > env.block.time);
+    let msg = unstake(
+        deps.branch(),
+        &env,
+        &saver,
+        &mut account,
+        state.unbonding_period,
+    )?
+    .ok_or(ContractError::NotStaked {})?;
+    ACCOUNTS.save(deps.storage, &saver, &account)?;
+
+    Ok(Response::new()
+        .add_message(msg)
+        .add_attribute("action", "unstake")
+        .add_attribute("owner", saver))
+}
+
+pub fn execute_compound(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let validators: Vec<String> = deps
+        .querier
+        .query_all_delegations(&env.contract.address)?
+        .into_iter()
+        .map(|delegation| delegation.validator)
+        .collect();
+    if validators.is_empty() {
+        return Err(ContractError::NotStaked {});
+    }
+    let denom = deps.querier.query_bonded_denom()?;
+    let balance = deps
+        .querier
+        .query_balance(&env.contract.address, denom)?
+        .amount;
+    let msgs: Vec<SubMsg> = validators
+        .iter()
+        .map(|validator| {
+            SubMsg::reply_on_success(
+                DistributionMsg::WithdrawDelegatorReward {
+                    validator: validator.clone(),
+                },
+                COMPOUND_REPLY_ID,
+            )
+        })
+        .collect();
+    COMPOUNDING.save(
+        deps.storage,
+        &Compounding {
+            balance,
+            validators,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attribute("action", "compound"))
+}
+
+pub fn execute_update_strategy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    strategy: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut state = STATE.load(deps.storage)?;
+    // only owner can choose the strategy
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    let strategy = strategy
+        .map(|strategy| deps.api.addr_validate(&strategy))
+        .transpose()?;
+    // it has to answer the strategy queries
+    if let Some(strategy) = &strategy {
+        Strategy(strategy.clone())
+            .query_value(&deps.querier, &env.contract.address)
+            .map_err(|_| ContractError::InvalidStrategy {
+                strategy: strategy.to_string(),
+            })?;
+    }
+    state.strategy = strategy;
+    STATE.save(deps.storage, &state)?;
+
+    Ok(Response::new().add_attribute("action", "update_strategy"))
+}
+
+pub fn execute_invest(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Coin,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    let strategy = Strategy(
+        STATE
+            .load(deps.storage)?
+            .strategy
+            .ok_or(ContractError::NoStrategy {})?,
+    );
+    if amount.amount.is_zero() {
+        return Err(ContractError::EmptyTransfer {});
+    }
+    // strategies are paid in native funds
+    if amount.denom.starts_with(CW20_PREFIX) {
+        return Err(ContractError::InvalidDenom {
+            denom: amount.denom,
+        });
+    }
+    let value = strategy.query_value(&deps.querier, &env.contract.address)?;
+    if value.denom != amount.denom {
+        return Err(ContractError::StrategyDenomMismatch {
+            expected: value.denom,
+            received: amount.denom,
+        });
+    }
+    if account.balance_of(&amount.denom) < amount.amount {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    if account.liquid_of(&amount.denom, env.block.time) < amount.amount {
+        return Err(ContractError::SavingsStaked {});
+    }
+
+    // shares are priced on the position before this deposit
+    let total = STRATEGY_SHARES
+        .may_load(deps.storage, strategy.addr())?
+        .unwrap_or_default();
+    let shares = if total.is_zero() {
+        amount.amount
+    } else if value.amount.is_zero() {
+        // new shares would be worth as much as the old ones
+        return Err(ContractError::StrategyWorthless {});
+    } else {
+        amount.amount.multiply_ratio(total, value.amount)
+    };
+    debit_savings(deps.storage, &info.sender, &mut account, &amount)?;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+    INVESTMENTS.update(
+        deps.storage,
+        (&info.sender, strategy.addr()),
+        |held| -> StdResult<_> { Ok(held.unwrap_or_default().checked_add(shares)?) },
+    )?;
+    STRATEGY_SHARES.save(deps.storage, strategy.addr(), &total.checked_add(shares)?)?;
+
+    Ok(Response::new()
+        .add_message(strategy.deposit(amount)?)
+        .add_attribute("action", "invest")
+        .add_attribute("strategy", strategy.addr())
+        .add_attribute("shares", shares.to_string()))
+}
+
+pub fn execute_divest(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    strategy: String,
+    shares: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    let strategy = Strategy(deps.api.addr_validate(&strategy)?);
+    let held = INVESTMENTS
+        .may_load(deps.storage, (&info.sender, strategy.addr()))?
+        .unwrap_or_default();
+    let shares = shares.unwrap_or(held);
+    if shares.is_zero() || shares > held {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    let total = STRATEGY_SHARES.load(deps.storage, strategy.addr())?;
+    let value = strategy.query_value(&deps.querier, &env.contract.address)?;
+    let withdrawn = Coin {
+        amount: value.amount.multiply_ratio(shares, total),
+        denom: value.denom,
+    };
+    let left = held.checked_sub(shares)?;
+    if left.is_zero() {
+        INVESTMENTS.remove(deps.storage, (&info.sender, strategy.addr()));
+    } else {
+        INVESTMENTS.save(deps.storage, (&info.sender, strategy.addr()), &left)?;
+    }
+    STRATEGY_SHARES.save(deps.storage, strategy.addr(), &total.checked_sub(shares)?)?;
+    credit_default_pot(deps.storage, &info.sender, &mut account, &withdrawn)?;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new()
+        .add_message(strategy.withdraw(withdrawn.amount)?)
+        .add_attribute("action", "divest")
+        .add_attribute("strategy", strategy.addr())
+        .add_attribute("withdrawn", withdrawn.to_string()))
+}
+
+pub fn execute_update_route(
+    deps: DepsMut,
+    info: MessageInfo,
+    offer_denom: String,
+    ask_denom: String,
+    pair: Option<String>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    // only owner can set the routes
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    // pairs only swap native denoms
+    if offer_denom == ask_denom
+        || offer_denom.starts_with(CW20_PREFIX)
+        || ask_denom.starts_with(CW20_PREFIX)
+    {
+        return Err(ContractError::NoRoute {
+            offer_denom,
+            ask_denom,
+        });
+    }
+    match pair {
+        Some(pair) => {
+            let pair = deps.api.addr_validate(&pair)?;
+            ROUTES.save(deps.storage, (&offer_denom, &ask_denom), &pair)?;
+        }
+        None => ROUTES.remove(deps.storage, (&offer_denom, &ask_denom)),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_route")
+        .add_attribute("offer_denom", offer_denom)
+        .add_attribute("ask_denom", ask_denom))
+}
+
+pub fn execute_update_dca(
+    deps: DepsMut,
+    info: MessageInfo,
+    dca: Option<Dca>,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    if let Some(dca) = &dca {
+        validate_price(dca.belief_price)?;
+        validate_slippage(dca.max_slippage)?;
+    }
+    account.dca = dca;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new().add_attribute("action", "update_dca"))
+}
+
+pub fn execute_swap(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Coin,
+    ask_denom: String,
+    belief_price: Decimal,
+    max_slippage: Decimal,
+) -> Result<Response, ContractError> {
+    let mut account = load_account(deps.storage, &info.sender)?;
+    if amount.amount.is_zero() {
+        return Err(ContractError::EmptyTransfer {});
+    }
+    let msg = swap_order(
+        deps.branch(),
+        &env,
+        &info.sender,
+        &mut account,
+        amount,
+        &ask_denom,
+        belief_price,
+        max_slippage,
+    )?;
+    ACCOUNTS.save(deps.storage, &info.sender, &account)?;
+
+    Ok(Response::new()
+        .add_submessage(msg)
+        .add_attribute("action", "swap"))
+}
+
+// Take offer out of the savings and swap it through its route, the reply
+// credits the output. The saver's belief_price sets the least it may pay out,
+// a swap paying less is reverted and the reply saves the offer again
+#[allow(clippy::too_many_arguments)]
+fn swap_order(
+    deps: DepsMut,
+    env: &Env,
+    saver: &Addr,
+    account: &mut Account,
+    offer: Coin,
+    ask_denom: &str,
+    belief_price: Decimal,
+    max_slippage: Decimal,
+) -> Result<SubMsg, ContractError> {
+    validate_price(belief_price)?;
+    validate_slippage(max_slippage)?;
+    let no_route = || ContractError::NoRoute {
+        offer_denom: offer.denom.clone(),
+        ask_denom: ask_denom.to_string(),
+    };
+    let pair = Pair(
+        ROUTES
+            .may_load(deps.storage, (&offer.denom, ask_denom))?
+            .ok_or_else(no_route)?,
+    );
+    if account.balance_of(&offer.denom) < offer.amount {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    if account.liquid_of(&offer.denom, env.block.time) < offer.amount {
+        return Err(ContractError::SavingsStaked {});
+    }
+    let min_out = offer.amount * (Decimal::one() / belief_price) * (Decimal::one() - max_slippage);
+
+    debit_savings(deps.storage, saver, account, &offer)?;
+    let mut swapping = SWAPPING.may_load(deps.storage)?.unwrap_or(Swapping {
+        balances: vec![],
+        orders: vec![],
+    });
+    if !swapping.balances.iter().any(|held| held.denom == ask_denom) {
+        let held = deps
+            .querier
+            .query_balance(&env.contract.address, ask_denom)?;
+        swapping.balances.push(held);
+    }
+    swapping.orders.push(SwapOrder {
+        saver: saver.clone(),
+        offer,
+        ask_denom: ask_denom.to_string(),
+        min_out,
+        pair: pair.addr().clone(),
+        belief_price,
+        max_slippage,
+    });
+    SWAPPING.save(deps.storage, &swapping)?;
+
+    Ok(SubMsg::reply_always(
+        WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::ExecuteSwapOrder {})?,
+            funds: vec![],
+        },
+        SWAP_REPLY_ID,
+    ))
+}
+
+// Swap the order being replied to next, the check runs after the pair paid out
+pub fn execute_swap_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    // only this contract swaps its orders
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    let swapping = SWAPPING.load(deps.storage)?;
+    let order = swapping
+        .orders
+        .first()
+        .ok_or_else(|| StdError::not_found("swap order"))?;
+
+    Ok(Response::new()
+        .add_message(Pair(order.pair.clone()).swap(
+            order.offer.clone(),
+            order.belief_price,
+            order.max_slippage,
+        )?)
+        .add_message(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::CheckSwapOrder {})?,
+            funds: vec![],
+        }))
+}
+
+pub fn execute_check_swap_order(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    let swapping = SWAPPING.load(deps.storage)?;
+    let order = swapping
+        .orders
+        .first()
+        .ok_or_else(|| StdError::not_found("swap order"))?;
+    let received = swap_output(deps.as_ref(), &env, &swapping, order)?;
+    // failing here reverts the swap along with it
+    if received < order.min_out {
+        return Err(ContractError::SlippageExceeded {
+            min_out: order.min_out,
+            received,
+        });
+    }
+    Ok(Response::new())
+}
+
+// What the order's swap added to the contract's ask denom balance
+fn swap_output(
+    deps: Deps,
+    env: &Env,
+    swapping: &Swapping,
+    order: &SwapOrder,
+) -> StdResult<Uint128> {
+    let held = deps
+        .querier
+        .query_balance(&env.contract.address, &order.ask_denom)?
+        .amount;
+    let before = swapping
+        .balances
+        .iter()
+        .find(|before| before.denom == order.ask_denom)
+        .ok_or_else(|| StdError::not_found("swap balance"))?;
+    held.checked_sub(before.amount).map_err(StdError::from)
+}
+
+// Time from which the delegation can be unbonded in time for the unlock
+fn unstake_at(account: &Account, unbonding_period: u64) -> Option<Timestamp> {
+    match &account.lock {
+        Some(LockPolicy::AtTime(time)) => Some(Timestamp::from_nanos(
+            time.nanos()
+                .saturating_sub(unbonding_period.saturating_mul(1_000_000_000)),
+        )),
+        _ => None,
+    }
+}
+
+pub fn execute_propose_owner(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+    expires_in: Option<u64>,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    // only owner can propose a new owner
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(expires_in) = expires_in {
+        validate_period(expires_in)?;
+    }
+    let pending = PendingOwner {
+        address: deps.api.addr_validate(&new_owner)?,
+        expires_at: expires_in.map(|seconds| env.block.time.plus_seconds(seconds)),
+    };
+    // the owner's account moves to the new owner, who can't already hold one
+    if ACCOUNTS.has(deps.storage, &state.owner) && ACCOUNTS.has(deps.storage, &pending.address) {
+        return Err(ContractError::AccountExists {});
+    }
+    PENDING_OWNER.save(deps.storage, &pending)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_owner")
+        .add_attribute("pending_owner", pending.address.to_string()))
+}
+
+fn validate_period(period: u64) -> Result<(), ContractError> {
+    if period > MAX_PERIOD {
+        return Err(ContractError::PeriodTooLong { max: MAX_PERIOD });
+    }
+    Ok(())
+}
+
+pub fn execute_accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner {})?;
+    // only the proposed owner can accept, from their own address
+    if info.sender != pending.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(expires_at) = pending.expires_at {
+        if env.block.time >= expires_at {
+            return Err(ContractError::OwnershipProposalExpired {});
+        }
+    }
+
+    // the savings follow the owner to the new address
+    let mut state = STATE.load(deps.storage)?;
+    move_account(deps.storage, &state.owner, &pending.address)?;
+    state.owner = pending.address.clone();
+    STATE.save(deps.storage, &state)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_ownership")
+        .add_attribute("owner", pending.address.to_string()))
+}
+
+// Re-key the account and everything kept per saver from one address to another
+fn move_account(storage: &mut dyn Storage, from: &Addr, to: &Addr) -> Result<(), ContractError> {
+    let account = match ACCOUNTS.may_load(storage, from)? {
+        Some(account) => account,
+        None => return Ok(()),
+    };
+    if ACCOUNTS.may_load(storage, to)?.is_some() {
+        return Err(ContractError::AccountExists {});
+    }
+    ACCOUNTS.remove(storage, from);
+    ACCOUNTS.save(storage, to, &account)?;
+    if let Some(delegation) = &account.delegation {
+        STAKERS.remove(storage, (&delegation.validator, from));
+        STAKERS.save(storage, (&delegation.validator, to), &Empty {})?;
+    }
+
+    let pots = POTS
+        .prefix(from)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (name, pot) in pots {
+        POTS.remove(storage, (from, name.as_str()));
+        POTS.save(storage, (to, name.as_str()), &pot)?;
+    }
+    let stats = STATS
+        .prefix(from)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (denom, stats) in stats {
+        STATS.remove(storage, (from, denom.as_str()));
+        STATS.save(storage, (to, denom.as_str()), &stats)?;
+    }
+    let deposits = DEPOSITS
+        .prefix(from)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, record) in deposits {
+        DEPOSITS.remove(storage, (from, id));
+        DEPOSITS.save(storage, (to, id), &record)?;
+    }
+    for request in load_withdrawals(storage, from)? {
+        WITHDRAWALS.remove(storage, (from, request.id));
+        WITHDRAWALS.save(storage, (to, request.id), &request)?;
+    }
+    let investments = INVESTMENTS
+        .prefix(from)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (strategy, shares) in investments {
+        INVESTMENTS.remove(storage, (from, &strategy));
+        INVESTMENTS.save(storage, (to, &strategy), &shares)?;
+    }
+    if let Some(request) = GUARDIAN_REQUESTS.may_load(storage, from)? {
+        GUARDIAN_REQUESTS.remove(storage, from);
+        GUARDIAN_REQUESTS.save(storage, to, &request)?;
+    }
+    let proposals = PROPOSALS
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, proposal)| proposal.owner == *from)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, mut proposal) in proposals {
+        proposal.owner = to.clone();
+        PROPOSALS.save(storage, id, &proposal)?;
+    }
+    if let Some(id) = INHERITANCE_PROPOSALS.may_load(storage, from)? {
+        INHERITANCE_PROPOSALS.remove(storage, from);
+        INHERITANCE_PROPOSALS.save(storage, to, &id)?;
+    }
+    Ok(())
+}
+
+pub fn execute_cancel_ownership_transfer(
+    deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let state = STATE.load(deps.storage)?;
+    // only owner can cancel a proposal
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if PENDING_OWNER.may_load(deps.storage)?.is_none() {
+        return Err(ContractError::NoPendingOwner {});
+    }
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_ownership_transfer"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        COMPOUND_REPLY_ID => reply_compound(deps, env),
+        SWAP_REPLY_ID => reply_swap(deps, env, msg.result),
+        id => Err(ContractError::UnknownReply { id }),
+    }
+}
+
+// Share what a validator paid out between the accounts delegating to it, then
+// delegate it again. The delegation in the response restores the balance
+// before the next validator's reply
+fn reply_compound(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut compounding = COMPOUNDING.load(deps.storage)?;
+    let validator = compounding.validators.remove(0);
+    if compounding.validators.is_empty() {
+        COMPOUNDING.remove(deps.storage);
+    } else {
+        COMPOUNDING.save(deps.storage, &compounding)?;
+    }
+    let denom = deps.querier.query_bonded_denom()?;
+    let claimed = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount
+        .saturating_sub(compounding.balance);
+
+    let res = Response::new()
+        .add_attribute("action", "compound_reply")
+        .add_attribute("validator", validator.clone())
+        .add_attribute("claimed", claimed.to_string());
+    let stakers = STAKERS
+        .prefix(&validator)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .map(|saver| {
+            let saver = saver?;
+            let account = ACCOUNTS.load(deps.storage, &saver)?;
+            Ok((saver, account))
+        })
+        .filter(|item: &StdResult<(Addr, Account)>| {
+            item.as_ref().map_or(true, |(_, account)| {
+                account
+                    .delegation
+                    .as_ref()
+                    .is_some_and(|delegation| delegation.amount.denom == denom)
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let total = stakers
+        .iter()
+        .try_fold(Uint128::zero(), |total, (_, account)| {
+            account.delegation.as_ref().map_or(Ok(total), |delegation| {
+                total.checked_add(delegation.amount.amount)
+            })
+        })?;
+    if claimed.is_zero() || total.is_zero() {
+        return Ok(res);
+    }
+
+    // by delegated amount, the last staker takes the rounding dust
+    let mut remaining = claimed;
+    let count = stakers.len();
+    for (i, (saver, mut account)) in stakers.into_iter().enumerate() {
+        let delegation = account
+            .delegation
+            .as_mut()
+            .ok_or(ContractError::NotStaked {})?;
+        let share = if i + 1 == count {
+            remaining
+        } else {
+            claimed.multiply_ratio(delegation.amount.amount, total)
+        };
+        remaining = remaining.checked_sub(share)?;
+        delegation.amount.amount = delegation.amount.amount.checked_add(share)?;
+        let reward = Coin {
+            denom: denom.clone(),
+            amount: share,
+        };
+        credit_default_pot(deps.storage, &saver, &mut account, &reward)?;
+        ACCOUNTS.save(deps.storage, &saver, &account)?;
+        update_stats(deps.storage, &saver, &denom, |stats| {
+            stats.total_compounded = stats.total_compounded.checked_add(share)?;
+            Ok(())
+        })?;
+    }
+    DELEGATED.update(deps.storage, &validator, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().checked_add(claimed)?)
+    })?;
+
+    Ok(res.add_message(StakingMsg::Delegate {
+        validator,
+        amount: Coin {
+            denom,
+            amount: claimed,
+        },
+    }))
+}
+
+// Save what the swap paid out, or put the offer back when the pair refused it
+fn reply_swap(deps: DepsMut, env: Env, result: SubMsgResult) -> Result<Response, ContractError> {
+    let mut swapping = SWAPPING.load(deps.storage)?;
+    let order = swapping.orders.remove(0);
+    let mut account = load_account(deps.storage, &order.saver)?;
+    let mut res = Response::new()
+        .add_attribute("action", "swap_reply")
+        .add_attribute("owner", order.saver.clone());
+
+    if let SubMsgResult::Err(err) = result {
+        credit_default_pot(deps.storage, &order.saver, &mut account, &order.offer)?;
+        res = res.add_attribute("swap_failed", err);
+    } else {
+        let received = swap_output(deps.as_ref(), &env, &swapping, &order)?;
+        // the next order measures from here
+        if let Some(before) = swapping
+            .balances
+            .iter_mut()
+            .find(|before| before.denom == order.ask_denom)
+        {
+            before.amount = before.amount.checked_add(received)?;
+        }
+        let output = Coin {
+            denom: order.ask_denom.clone(),
+            amount: received,
+        };
+        let overflow = credit_savings(deps.storage, &order.saver, &mut account, &output)?;
+        res = res.add_attribute("received", output.to_string());
+        if !overflow.is_zero() {
+            res = res.add_messages(send_msgs(
+                &order.saver,
+                vec![Coin {
+                    denom: order.ask_denom,
+                    amount: overflow,
+                }],
+            )?);
+        }
+    }
+    ACCOUNTS.save(deps.storage, &order.saver, &account)?;
+    if swapping.orders.is_empty() {
+        SWAPPING.remove(deps.storage);
+    } else {
+        SWAPPING.save(deps.storage, &swapping)?;
+    }
+
+    Ok(res)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // only upgrade this contract, never downgrade it
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::InvalidContract {
+            stored: stored.contract,
+        });
+    }
+    let stored_version = parse_version(&stored.version)?;
+    let current_version = parse_version(CONTRACT_VERSION)?;
+    if stored_version > current_version {
+        return Err(ContractError::CannotDowngrade {
+            stored: stored.version,
+            current: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION);
+
+    // move the single-owner state of earlier releases into the owner's account,
+    // kept under config by the oldest ones and under the state key after that
+    let legacy = if stored_version < parse_version(MULTI_SAVER_VERSION)? {
+        match config_read(deps.storage).may_load()? {
+            Some(legacy) => Some(legacy),
+            None => LEGACY_STATE.may_load(deps.storage)?,
+        }
+    } else {
+        None
+    };
+    if let Some(legacy) = legacy {
+        STATE.save(
+            deps.storage,
+            &State {
+                owner: legacy.owner.clone(),
+                amount_received: legacy.amount_received,
+                pause_guardian: None,
+                validator: None,
+                unbonding_period: 0,
+                strategy: None,
+            },
+        )?;
+        if ACCOUNTS.may_load(deps.storage, &legacy.owner)?.is_none() {
+            // the whole balance belonged to the single owner
+            // the rate was never validated, keep it between 1% and 100%
+            let savings_rate = u16::from(legacy.savings_rate.clamp(1, 100)) * 100;
+            let mut account = new_account(env.block.time, savings_rate, None, None, None, None)?;
+            let balance = deps.querier.query_all_balances(&env.contract.address)?;
+            for saved in balance.iter() {
+                credit_savings(deps.storage, &legacy.owner, &mut account, saved)?;
+            }
+            ACCOUNTS.save(deps.storage, &legacy.owner, &account)?;
+        }
+        config(deps.storage).remove();
+        res = res.add_attribute("migrated_owner", legacy.owner);
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(res)
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version).map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetBalance { address } => to_binary(&query_balance(deps, env, address)?),
+        QueryMsg::GetOwnership {} => to_binary(&query_ownership(deps)?),
+        QueryMsg::GetPause {} => to_binary(&query_pause(deps)?),
+        QueryMsg::GetLock { address } => to_binary(&query_lock(deps, env, address)?),
+        QueryMsg::GetProgress { address } => to_binary(&query_progress(deps, address)?),
+        QueryMsg::Deposits {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_deposits(deps, address, start_after, limit)?),
+        QueryMsg::Stats { address } => to_binary(&query_stats(deps, address)?),
+        QueryMsg::Proposals {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_proposals(deps, env, address, start_after, limit)?),
+        QueryMsg::GetGuardian { address } => to_binary(&query_guardian(deps, address)?),
+        QueryMsg::GetApprovers { address } => to_binary(&query_approvers(deps, address)?),
+        QueryMsg::GetInheritance { address } => to_binary(&query_inheritance(deps, env, address)?),
+        QueryMsg::Withdrawals { address } => to_binary(&query_withdrawals(deps, address)?),
+        QueryMsg::GetStake { address } => to_binary(&query_stake(deps, address)?),
+        QueryMsg::Token { token } => to_binary(&query_token(deps, token)?),
+        QueryMsg::Route {
+            offer_denom,
+            ask_denom,
+        } => to_binary(&query_route(deps, offer_denom, ask_denom)?),
+        QueryMsg::Investments { address } => to_binary(&query_investments(deps, env, address)?),
+        QueryMsg::Pots { address } => to_binary(&query_pots(deps, address)?),
+        QueryMsg::SimulateTransfer {
+            address,
+            funds,
+            savings_rate,
+        } => to_binary(&query_simulate_transfer(
+            deps,
+            address,
+            funds,
+            savings_rate,
+        )?),
+    }
+}
+
+fn query_account(deps: Deps, address: &str) -> StdResult<Account> {
+    let saver = deps.api.addr_validate(address)?;
+    ACCOUNTS.load(deps.storage, &saver)
+}
+
+fn query_balance(deps: Deps, env: Env, address: String) -> StdResult<BalanceResponse> {
+    let account = query_account(deps, &address)?;
+    let liquid = account
+        .balance
+        .iter()
+        .map(|saved| Coin {
+            denom: saved.denom.clone(),
+            amount: account.liquid_of(&saved.denom, env.block.time),
+        })
+        .filter(|liquid| !liquid.amount.is_zero())
+        .collect();
+    let delegated = account
+        .delegation
+        .iter()
+        .map(|delegation| delegation.amount.clone())
+        .collect();
+    let unbonding = account
+        .unbonding
+        .iter()
+        .filter(|unbonding| unbonding.completes_at > env.block.time)
+        .map(|unbonding| unbonding.amount.clone())
+        .collect();
+    Ok(BalanceResponse {
+        balance: account.balance,
+        liquid,
+        delegated,
+        unbonding,
+    })
+}
+
+fn query_investments(deps: Deps, env: Env, address: String) -> StdResult<InvestmentsResponse> {
+    let saver = deps.api.addr_validate(&address)?;
+    let investments = INVESTMENTS
+        .prefix(&saver)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (strategy, shares) = item?;
+            let total = STRATEGY_SHARES.load(deps.storage, &strategy)?;
+            let value =
+                Strategy(strategy.clone()).query_value(&deps.querier, &env.contract.address)?;
+            Ok(Investment {
+                strategy,
+                shares,
+                value: Coin {
+                    amount: value.amount.multiply_ratio(shares, total),
+                    denom: value.denom,
+                },
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(InvestmentsResponse { investments })
+}
+
+fn query_token(deps: Deps, token: String) -> StdResult<TokenResponse> {
+    let token = deps.api.addr_validate(&token)?;
+    Ok(TokenResponse {
+        accepted: TOKENS.has(deps.storage, &token),
+    })
+}
+
+fn query_route(deps: Deps, offer_denom: String, ask_denom: String) -> StdResult<RouteResponse> {
+    Ok(RouteResponse {
+        pair: ROUTES.may_load(deps.storage, (&offer_denom, &ask_denom))?,
+    })
+}
+
+fn query_stake(deps: Deps, address: String) -> StdResult<StakeResponse> {
+    let account = query_account(deps, &address)?;
+    let state = STATE.load(deps.storage)?;
+    Ok(StakeResponse {
+        unstake_at: account
+            .delegation
+            .as_ref()
+            .and(unstake_at(&account, state.unbonding_period)),
+        delegation: account.delegation,
+        unbonding: account.unbonding,
+    })
+}
+
+fn query_deposits(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<DepositsResponse> {
+    let saver = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let deposits = DEPOSITS
+        .prefix(&saver)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, record)| record))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(DepositsResponse { deposits })
+}
+
+fn query_stats(deps: Deps, address: String) -> StdResult<StatsResponse> {
+    let saver = deps.api.addr_validate(&address)?;
+    let stats = STATS
+        .prefix(&saver)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, stats)| stats))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(StatsResponse { stats })
+}
+
+// Proposals of the saver that can still be approved
+fn query_proposals(
+    deps: Deps,
+    env: Env,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalsResponse> {
+    let saver = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive_int);
+    let proposals = PROPOSALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .map(|item| item.map(|(_, proposal)| proposal))
+        .filter(|item| match item {
+            Ok(proposal) => proposal.owner == saver && env.block.time < proposal.expires_at,
+            Err(_) => true,
+        })
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ProposalsResponse { proposals })
+}
+
+fn query_guardian(deps: Deps, address: String) -> StdResult<GuardianResponse> {
+    let saver = deps.api.addr_validate(&address)?;
+    let account = ACCOUNTS.load(deps.storage, &saver)?;
+    Ok(GuardianResponse {
+        guardian: account.guardian,
+        pending_change: account.pending_guardian,
+        pending_request: GUARDIAN_REQUESTS.may_load(deps.storage, &saver)?,
+    })
+}
+
+fn query_approvers(deps: Deps, address: String) -> StdResult<ApproversResponse> {
+    let account = query_account(deps, &address)?;
+    Ok(ApproversResponse {
+        approvers: account.approvers,
+        pending_change: account.pending_approvers,
+    })
+}
+
+fn query_inheritance(deps: Deps, env: Env, address: String) -> StdResult<InheritanceResponse> {
+    let account = query_account(deps, &address)?;
+    let claimable_at = account.inheritance.as_ref().map(|inheritance| {
+        account
+            .last_activity
+            .plus_seconds(inheritance.inactivity_period)
+    });
+    Ok(InheritanceResponse {
+        beneficiary: account
+            .inheritance
+            .as_ref()
+            .map(|inheritance| inheritance.beneficiary.clone()),
+        inactivity_period: account
+            .inheritance
+            .as_ref()
+            .map(|inheritance| inheritance.inactivity_period),
+        last_activity: account.last_activity,
+        claimable_at,
+        remaining_seconds: claimable_at
+            .map(|time| time.seconds().saturating_sub(env.block.time.seconds())),
+    })
+}
+
+fn query_withdrawals(deps: Deps, address: String) -> StdResult<WithdrawalsResponse> {
+    let saver = deps.api.addr_validate(&address)?;
+    let withdrawals = load_withdrawals(deps.storage, &saver)?;
+    Ok(WithdrawalsResponse { withdrawals })
+}
+
+fn query_pots(deps: Deps, address: String) -> StdResult<PotsResponse> {
+    let saver = deps.api.addr_validate(&address)?;
+    let pots = load_pots(deps.storage, &saver)?;
+    Ok(PotsResponse { pots })
+}
+
+fn query_simulate_transfer(
+    deps: Deps,
+    address: String,
+    funds: Vec<Coin>,
+    savings_rate: Option<u16>,
+) -> StdResult<SimulateTransferResponse> {
+    let saver = deps.api.addr_validate(&address)?;
+    let account = ACCOUNTS.load(deps.storage, &saver)?;
+    simulate_transfer(deps.storage, &saver, &account, funds, savings_rate)
+        .map_err(|err| StdError::generic_err(err.to_string()))
+}
+
+// Run the transfer arithmetic against copies of the pots
+fn simulate_transfer(
+    storage: &dyn Storage,
+    saver: &Addr,
+    account: &Account,
+    funds: Vec<Coin>,
+    savings_rate: Option<u16>,
+) -> Result<SimulateTransferResponse, ContractError> {
+    let savings_rate = savings_rate.unwrap_or(account.savings_rate);
+    validate_savings_rate(savings_rate)?;
+    let mut pots = load_pots(storage, saver)?;
+    let before = pots.clone();
+
+    let mut payout = vec![];
+    let mut returned = vec![];
+    for fund in funds.iter().filter(|fund| !fund.amount.is_zero()) {
+        let amount = payout_amount(fund.amount, savings_rate, &account.rounding)?;
+        let overflow = allocate(
+            &account.allocation,
+            &mut pots,
+            &Coin {
+                denom: fund.denom.clone(),
+                amount: fund.amount.checked_sub(amount)?,
+            },
+        )?;
+        if !amount.is_zero() {
+            payout.push(Coin {
+                denom: fund.denom.clone(),
+                amount,
+            });
+        }
+        if !overflow.is_zero() {
+            returned.push(Coin {
+                denom: fund.denom.clone(),
+                amount: overflow,
+            });
+        }
+    }
+
+    // report what each pot gains
+    let pots = pots
+        .iter()
+        .zip(before.iter())
+        .map(|(after, before)| PotShare {
+            name: after.name.clone(),
+            amount: after
+                .balance
+                .iter()
+                .filter_map(|saved| {
+                    let gained = saved.amount - before.balance_of(&saved.denom);
+                    if gained.is_zero() {
+                        None
+                    } else {
+                        Some(Coin {
+                            denom: saved.denom.clone(),
+                            amount: gained,
+                        })
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+    Ok(SimulateTransferResponse {
+        payout,
+        returned,
+        pots,
+    })
+}
+
+fn query_ownership(deps: Deps) -> StdResult<OwnershipResponse> {
+    let state = STATE.load(deps.storage)?;
+    let pending = PENDING_OWNER.may_load(deps.storage)?;
+    Ok(OwnershipResponse {
+        owner: state.owner,
+        pending_owner: pending.as_ref().map(|p| p.address.clone()),
+        pending_expires_at: pending.and_then(|p| p.expires_at),
+    })
+}
+
+fn query_pause(deps: Deps) -> StdResult<PauseResponse> {
+    let pause = PAUSE.may_load(deps.storage)?.unwrap_or_default();
+    Ok(PauseResponse {
+        pause_guardian: STATE.load(deps.storage)?.pause_guardian,
+        deposits: pause.deposits,
+        withdrawals: pause.withdrawals,
+        paused_by: pause.paused_by,
+        paused_at: pause.paused_at,
+    })
+}
+
+fn query_lock(deps: Deps, env: Env, address: String) -> StdResult<LockResponse> {
+    let account = query_account(deps, &address)?;
+    let (remaining_seconds, remaining_blocks) = match &account.lock {
+        Some(LockPolicy::AtTime(time)) => (
+            Some(time.seconds().saturating_sub(env.block.time.seconds())),
+            None,
+        ),
+        Some(LockPolicy::AtHeight(height)) => (None, Some(height.saturating_sub(env.block.height))),
+        None => (None, None),
+    };
+    Ok(LockResponse {
+        locked: account
+            .lock
+            .as_ref()
+            .is_some_and(|lock| lock.is_locked(&env.block)),
+        unlocks_at: account.lock,
+        remaining_seconds,
+        remaining_blocks,
+    })
+}
+
+fn query_progress(deps: Deps, address: String) -> StdResult<ProgressResponse> {
+    let account = query_account(deps, &address)?;
+    let goal = account
+        .goal
+        .clone()
+        .ok_or_else(|| StdError::not_found("SavingsGoal"))?;
+    let saved = Coin {
+        denom: goal.target.denom.clone(),
+        amount: account.balance_of(&goal.target.denom),
+    };
+    // a released goal stays complete even though its funds left
+    let percent_complete = if goal.reached || saved.amount >= goal.target.amount {
+        Decimal::percent(10000)
+    } else {
+        Decimal::from_ratio(
+            saved.amount.checked_mul(Uint128::new(100))?,
+            goal.target.amount,
+        )
+    };
+    Ok(ProgressResponse {
+        saved,
+        target: goal.target,
+        percent_complete,
+        reached: goal.reached,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use cosmwasm_std::{
+        coin, coins,
+        testing::{mock_dependencies, mock_env, mock_info},
+        Addr, SubMsg, Validator,
+    };
+    use cw_multi_test::{
+        App, BankSudo, ContractWrapper, Executor, StakingInfo, StakingSudo, SudoMsg,
+    };
+
+    use crate::msg::{ReceiveMsg, RecipientWeight};
+    use crate::state::LegacyState;
+    use crate::testing::{
+        mock_money_market, mock_pair, MoneyMarketInstantiateMsg, MoneyMarketSudoMsg,
+        PairInstantiateMsg,
+    };
+
+    const OWNER: &str = "saver";
+
+    #[test]
+    fn try_instantiate() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &coins(2, "BTC"));
+
+        let msg = InstantiateMsg {
+            owner: OWNER.to_string(),
+            savings_rate: 1500,
+            rounding: None,
+            lock: None,
+            goal: None,
+            split: None,
+        };
+        let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "instantiate"), res.attributes[0]);
+
+        let state = STATE.load(&deps.storage);
+
+        assert_eq!(
+            state,
+            Ok(State {
+                owner: Addr::unchecked(OWNER),
+                amount_received: coins(2, "BTC"),
+                pause_guardian: None,
+                validator: None,
+                unbonding_period: 0,
+                strategy: None,
+            })
+        );
+
+        // the owner's account is opened with the instantiate settings
+        let account = ACCOUNTS.load(&deps.storage, &Addr::unchecked(OWNER));
+        assert_eq!(
+            account,
+            Ok(Account {
+                savings_rate: 1500,
+                rounding: RoundingMode::Floor,
+                lock: None,
+                goal: None,
+                split: None,
+                allocation: AllocationMode::Fixed,
+                withdrawal_delay: 0,
+                pending_withdrawal_delay: None,
+                inheritance: None,
+                penalty: None,
+                guardian: None,
+                pending_guardian: None,
+                approvers: None,
+                pending_approvers: None,
+                recent_withdrawals: vec![],
+                last_activity: mock_env().block.time,
+                delegation: None,
+                unbonding: vec![],
+                dca: None,
+                balance: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn try_transfer() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &[]);
+
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+
+        // only account holders can transfer
+        let info = mock_info("anyone", &coins(1, "BTC"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(1500),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::AccountNotFound {});
+        // can't receive empty funds
+        let info = mock_info(OWNER, &coins(0, "BTC"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(1500),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyTransfer {});
+
+        // savings must be above 0 and less than 100
+        let info = mock_info(OWNER, &coins(2, "BTC"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(10100),
+        };
+
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSavingsRate {});
+
+        // works
+        let info = mock_info(OWNER, &coins(8500, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(1500),
+        };
+
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(7225, "UST"),
+            }),
+        );
+
+        // fractional rates round the payout down, the remainder is saved
+        let info = mock_info(OWNER, &coins(1001, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(1250),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(875, "UST"),
+            }),
+        );
+
+        // ceil rounds the payout up
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: None,
+            rounding: Some(RoundingMode::Ceil),
+            withdrawal_delay: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let info = mock_info(OWNER, &coins(1001, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(1250),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(876, "UST"),
+            }),
+        );
+
+        // banker's rounding sends halves to the even neighbour
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: None,
+            rounding: Some(RoundingMode::Bankers),
+            withdrawal_delay: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let info = mock_info(OWNER, &[coin(2, "UST"), coin(6, "BTC")]);
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(2500),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: vec![coin(2, "UST"), coin(4, "BTC")],
+            }),
+        );
+
+        // a tiny transfer can be saved entirely
+        let info = mock_info(OWNER, &coins(1, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(9000),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // huge amounts fail instead of overflowing
+        let info = mock_info(OWNER, &coins(u128::MAX, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(1500),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::Overflow(_)));
+    }
+
+    #[test]
+    fn try_flush() {
+        let mut deps = mock_dependencies();
+        let info = mock_info("anyone", &coins(1000, "ATOM"));
+
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+
+        // only account holders can flush
+        let info = mock_info("anyone", &[]);
+
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Flush {}).unwrap_err();
+        assert_eq!(err, ContractError::AccountNotFound {});
+
+        // can't flush an empty balance, even if the contract holds funds
+        let env = mock_env();
+        let info = mock_info(OWNER, &[]);
+        deps.querier
+            .update_balance(&env.contract.address, coins(1000, "ATOM"));
+
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Flush {}).unwrap_err();
+        assert_eq!(err, ContractError::EmptyBalance {});
+
+        // works
+        let info = mock_info(OWNER, &coins(2000, "ETH"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info(OWNER, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Flush {}).unwrap();
+        assert_eq!(1, res.messages.len());
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(300, "ETH"),
+            })
+        );
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert_eq!(res.balance, vec![]);
+    }
+
+    #[test]
+    fn try_isolated_accounts() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+
+        // anyone can open an account with their own settings
+        let msg = ExecuteMsg::OpenAccount {
+            savings_rate: 5000,
+            rounding: None,
+            lock: None,
+            goal: None,
+            split: None,
+            penalty: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info("other", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::AccountExists {});
+
+        for (saver, amount) in [(OWNER, 1000), ("other", 400)] {
+            let msg = ExecuteMsg::Transfer {
+                received_funds: None,
+                savings_rate: None,
+            };
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(saver, &coins(amount, "UST")),
+                msg,
+            )
+            .unwrap();
+        }
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert_eq!(res.balance, coins(150, "UST"));
+        let res = query_balance(deps.as_ref(), mock_env(), "other".to_string()).unwrap();
+        assert_eq!(res.balance, coins(200, "UST"));
+
+        // a flush only pays out the sender's own savings
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: "other".to_string(),
+                amount: coins(200, "UST"),
+            })
+        );
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert_eq!(res.balance, coins(150, "UST"));
+    }
+
+    #[test]
+    fn try_ownership_transfer() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+
+        // expiries far enough out to overflow the block time are refused
+        let msg = ExecuteMsg::ProposeOwner {
+            new_owner: "new_wallet".to_string(),
+            expires_in: Some(u64::MAX),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::PeriodTooLong { max: MAX_PERIOD });
+
+        // only owner can propose
+        let msg = ExecuteMsg::ProposeOwner {
+            new_owner: "new_wallet".to_string(),
+            expires_in: Some(3600),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // proposed owner has no power until accepting
+        let res = query_ownership(deps.as_ref()).unwrap();
+        assert_eq!(res.owner, Addr::unchecked(OWNER));
+        assert_eq!(res.pending_owner, Some(Addr::unchecked("new_wallet")));
+
+        // only the proposed owner can accept
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // can't accept after expiry
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(3600);
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("new_wallet", &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::OwnershipProposalExpired {});
+
+        // works, and the savings move to the new wallet
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &coins(1000, "UST")),
+            msg,
+        )
+        .unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("new_wallet", &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap();
+        let res = query_ownership(deps.as_ref()).unwrap();
+        assert_eq!(res.owner, Addr::unchecked("new_wallet"));
+        assert_eq!(res.pending_owner, None);
+
+        let res = query_balance(deps.as_ref(), mock_env(), "new_wallet".to_string()).unwrap();
+        assert_eq!(res.balance, coins(150, "UST"));
+        let res = query_stats(deps.as_ref(), "new_wallet".to_string()).unwrap();
+        assert_eq!(res.stats[0].total_saved, Uint128::new(150));
+        let res = query_deposits(deps.as_ref(), "new_wallet".to_string(), None, None).unwrap();
+        assert_eq!(res.deposits.len(), 1);
+
+        // old owner lost access
+        let msg = ExecuteMsg::ProposeOwner {
+            new_owner: OWNER.to_string(),
+            expires_in: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AccountNotFound {});
+
+        // cancel clears the proposal
+        let msg = ExecuteMsg::ProposeOwner {
+            new_owner: OWNER.to_string(),
+            expires_in: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("new_wallet", &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("new_wallet", &[]),
+            ExecuteMsg::CancelOwnershipTransfer {},
+        )
+        .unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::NoPendingOwner {});
+
+        // an address with its own account can't be proposed, and opening one blocks accepting
+        let open = ExecuteMsg::OpenAccount {
+            savings_rate: 1000,
+            rounding: None,
+            lock: None,
+            goal: None,
+            split: None,
+            penalty: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("other", &[]),
+            open.clone(),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::ProposeOwner {
+            new_owner: "other".to_string(),
+            expires_in: None,
+        };
+        let err =
+            execute(deps.as_mut(), mock_env(), mock_info("new_wallet", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::AccountExists {});
+
+        let msg = ExecuteMsg::ProposeOwner {
+            new_owner: "third".to_string(),
+            expires_in: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("new_wallet", &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info("third", &[]), open).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("third", &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AccountExists {});
+    }
+
+    #[test]
+    fn try_flush_locked() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let unlock_time = env.block.time.plus_seconds(86400);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: Some(LockPolicy::AtTime(unlock_time)),
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &coins(2000, "ETH")),
+            msg,
+        )
+        .unwrap();
+
+        // can't flush before the unlock time
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StillLocked {
+                unlocks_at: LockPolicy::AtTime(unlock_time)
+            }
+        );
+        let res = query_lock(deps.as_ref(), env.clone(), OWNER.to_string()).unwrap();
+        assert!(res.locked);
+        assert_eq!(res.remaining_seconds, Some(86400));
+
+        // works once unlocked
+        let mut env = env;
+        env.block.time = unlock_time;
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+        let res = query_lock(deps.as_ref(), env, OWNER.to_string()).unwrap();
+        assert!(!res.locked);
+        assert_eq!(res.remaining_seconds, Some(0));
+    }
+
+    #[test]
+    fn try_goal_release() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 5000,
+                rounding: None,
+                lock: None,
+                goal: Some(coin(5000, "USDC")),
+                split: None,
+            },
+        )
+        .unwrap();
+
+        // first deposit leaves 3000 saved once the payout goes out
+        let info = mock_info(OWNER, &coins(6000, "USDC"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(5000),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(1, res.messages.len());
+
+        // progress is read from the account balance
+        let res = query_progress(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert_eq!(res.percent_complete, Decimal::percent(6000));
+        assert!(!res.reached);
+
+        // second deposit reaches the goal and releases the savings
+        let info = mock_info(OWNER, &coins(4000, "USDC"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(5000),
+        };
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(2, res.messages.len());
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(5000, "USDC"),
+            })
+        );
+
+        let res = query_progress(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert_eq!(res.percent_complete, Decimal::percent(10000));
+        assert!(res.reached);
+    }
+
+    #[test]
+    fn try_goal_locked() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let unlock_time = env.block.time.plus_seconds(86400);
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 5000,
+                rounding: None,
+                lock: Some(LockPolicy::AtTime(unlock_time)),
+                goal: Some(coin(5000, "USDC")),
+                split: None,
+            },
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+
+        // a goal met while locked keeps the savings in the contract
+        let info = mock_info(OWNER, &coins(12000, "USDC"));
+        let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
+        assert_eq!(1, res.messages.len());
+        let res = query_progress(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert_eq!(res.saved, coin(6000, "USDC"));
+        assert_eq!(res.percent_complete, Decimal::percent(10000));
+        assert!(!res.reached);
+
+        // the first transfer after the unlock releases them
+        let mut env = env;
+        env.block.time = unlock_time;
+        let info = mock_info(OWNER, &coins(2000, "USDC"));
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(7000, "USDC"),
+            })
+        );
+        let res = query_progress(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert!(res.reached);
+    }
+
+    #[test]
+    fn try_transfer_attached_funds() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+
+        // can't claim funds that weren't attached
+        let info = mock_info(OWNER, &coins(100, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: Some(coins(1000, "UST")),
+            savings_rate: Some(1500),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::FundsMismatch {
+                expected: coins(1000, "UST"),
+                received: coins(100, "UST"),
+            }
+        );
+
+        // nothing attached
+        let info = mock_info(OWNER, &[]);
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(1500),
+        };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::EmptyTransfer {});
+
+        // several coins are split in one message
+        let funds = vec![coin(1000, "ATOM"), coin(200, "UST")];
+        let info = mock_info(OWNER, &funds);
+        let msg = ExecuteMsg::Transfer {
+            received_funds: Some(vec![coin(200, "UST"), coin(1000, "ATOM")]),
+            savings_rate: Some(1500),
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: vec![coin(850, "ATOM"), coin(170, "UST")],
+            })]
+        );
+    }
+
+    #[test]
+    fn try_stored_savings_rate() {
+        let mut deps = mock_dependencies();
+        let res = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 2000,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(("rate", "2000"), res.attributes[2]);
+
+        // stored rate is used without an override
+        let info = mock_info(OWNER, &coins(1000, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(("rate", "2000"), res.attributes[1]);
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(800, "UST"),
+            })
+        );
+
+        // only account holders can update their config
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: Some(4000),
+            rounding: None,
+            withdrawal_delay: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AccountNotFound {});
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::UpdateConfig {
+                savings_rate: Some(0),
+                rounding: None,
+                withdrawal_delay: None,
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidSavingsRate {});
+
+        // new stored rate applies
+        let info = mock_info(OWNER, &coins(1000, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(("rate", "4000"), res.attributes[1]);
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(600, "UST"),
+            })
+        );
+    }
+
+    #[test]
+    fn try_deposit_ledger() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+
+        let info = mock_info(OWNER, &[coin(1000, "ATOM"), coin(200, "UST")]);
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let info = mock_info(OWNER, &coins(50, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: Some(10000),
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        let env = mock_env();
+        let res = query_deposits(deps.as_ref(), OWNER.to_string(), None, None).unwrap();
+        assert_eq!(3, res.deposits.len());
+        assert_eq!(
+            res.deposits[0],
+            DepositRecord {
+                id: 1,
+                timestamp: env.block.time,
+                height: env.block.height,
+                denom: "ATOM".to_string(),
+                gross_amount: Uint128::new(1000),
+                saved_amount: Uint128::new(150),
+                paid_out_amount: Uint128::new(850),
+                savings_rate: 1500,
+            }
+        );
+        assert_eq!(res.deposits[2].saved_amount, Uint128::new(50));
+        assert_eq!(res.deposits[2].paid_out_amount, Uint128::zero());
+
+        // pagination
+        let res = query_deposits(deps.as_ref(), OWNER.to_string(), Some(1), Some(1)).unwrap();
+        assert_eq!(1, res.deposits.len());
+        assert_eq!(2, res.deposits[0].id);
+        assert_eq!("UST", res.deposits[0].denom);
+    }
+
+    #[test]
+    fn try_stats() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            let info = mock_info(OWNER, &coins(1000, "UST"));
+            let msg = ExecuteMsg::Transfer {
+                received_funds: None,
+                savings_rate: None,
+            };
+            execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        }
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap();
+
+        let res = query_stats(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert_eq!(
+            res.stats,
+            vec![DenomStats {
+                denom: "UST".to_string(),
+                total_received: Uint128::new(2000),
+                total_saved: Uint128::new(300),
+                total_paid_out: Uint128::new(1700),
+                total_withdrawn: Uint128::new(300),
+                total_penalty: Uint128::zero(),
+                total_compounded: Uint128::zero(),
+                transfers: 2,
+            }]
+        );
+    }
+
+    #[test]
+    fn try_migrate() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        // only this contract can be migrated
+        set_contract_version(&mut deps.storage, "crates.io:other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidContract {
+                stored: "crates.io:other".to_string()
+            }
+        );
+
+        // never downgrade
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotDowngrade {
+                stored: "99.0.0".to_string(),
+                current: CONTRACT_VERSION.to_string(),
+            }
+        );
+
+        // legacy config is moved into the owner's account
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.0.1").unwrap();
+        config(&mut deps.storage)
+            .save(&LegacyState {
+                owner: Addr::unchecked(OWNER),
+                amount_received: coins(2, "BTC"),
+                savings_rate: 15,
+            })
+            .unwrap();
+        deps.querier
+            .update_balance(&env.contract.address, coins(500, "UST"));
+        migrate(deps.as_mut(), env, MigrateMsg {}).unwrap();
+
+        assert_eq!(config_read(&deps.storage).may_load().unwrap(), None);
+        let state = STATE.load(&deps.storage).unwrap();
+        assert_eq!(state.owner, Addr::unchecked(OWNER));
+        let account = ACCOUNTS
+            .load(&deps.storage, &Addr::unchecked(OWNER))
+            .unwrap();
+        assert_eq!(account.savings_rate, 1500);
+        assert_eq!(account.balance, coins(500, "UST"));
+        let version = get_contract_version(&deps.storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        // the current layout is left as it is, whatever sits under the legacy keys
+        config(&mut deps.storage)
+            .save(&LegacyState {
+                owner: Addr::unchecked("anyone"),
+                amount_received: vec![],
+                savings_rate: 15,
+            })
+            .unwrap();
+        migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let state = STATE.load(&deps.storage).unwrap();
+        assert_eq!(state.owner, Addr::unchecked(OWNER));
+    }
+
+    #[test]
+    fn try_migrate_baseline() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+
+        // the first release kept its state under the state key, with any rate
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+        LEGACY_STATE
+            .save(
+                &mut deps.storage,
+                &LegacyState {
+                    owner: Addr::unchecked(OWNER),
+                    amount_received: coins(2, "BTC"),
+                    savings_rate: 0,
+                },
+            )
+            .unwrap();
+        deps.querier
+            .update_balance(&env.contract.address, coins(500, "UST"));
+        migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+
+        let state = STATE.load(&deps.storage).unwrap();
+        assert_eq!(state.owner, Addr::unchecked(OWNER));
+        assert_eq!(state.amount_received, coins(2, "BTC"));
+        let account = ACCOUNTS
+            .load(&deps.storage, &Addr::unchecked(OWNER))
+            .unwrap();
+        assert_eq!(account.savings_rate, 100);
+        assert_eq!(account.balance, coins(500, "UST"));
+
+        // the owner can reach the savings again
+        let res = execute(
+            deps.as_mut(),
+            env,
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0],
+            SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(500, "UST"),
+            })
+        );
+    }
+
+    #[test]
+    fn try_weighted_split() {
+        let mut deps = mock_dependencies();
+        let split = SpendingSplitMsg {
+            recipients: vec![
+                RecipientWeight {
+                    address: "checking".to_string(),
+                    weight: 7000,
+                },
+                RecipientWeight {
+                    address: "partner".to_string(),
+                    weight: 3000,
+                },
+            ],
+            dust_recipient: "partner".to_string(),
+        };
+
+        // weights must add up to 100%
+        let mut bad_split = split.clone();
+        bad_split.recipients[1].weight = 2000;
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: Some(bad_split),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidSplit {
+                reason: "weights add up to 9000 basis points".to_string()
+            }
+        );
+
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 1500,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: Some(split),
+            },
+        )
+        .unwrap();
+
+        // 85 is paid out, 59.5 and 25.5 round down and the dust goes to the partner
+        let info = mock_info(OWNER, &coins(100, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "checking".to_string(),
+                    amount: coins(59, "UST"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "partner".to_string(),
+                    amount: coins(26, "UST"),
+                }),
+            ]
+        );
+
+        // the owner can go back to being paid directly
+        let msg = ExecuteMsg::UpdateSplit { split: None };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let info = mock_info(OWNER, &coins(100, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(85, "UST"),
+            })]
+        );
+    }
+
+    #[test]
+    fn try_pots() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 10000,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+
+        for name in ["emergency", "vacation"] {
+            let msg = ExecuteMsg::CreatePot {
+                name: name.to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::CreatePot {
+            name: "vacation".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::PotExists {
+                name: "vacation".to_string()
+            }
+        );
+
+        // allocations can't exceed 100%
+        let msg = ExecuteMsg::SetAllocations {
+            allocations: vec![
+                PotAllocation {
+                    name: "emergency".to_string(),
+                    allocation: 6000,
+                },
+                PotAllocation {
+                    name: "vacation".to_string(),
+                    allocation: 5000,
+                },
+            ],
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidAllocation {});
+        let msg = ExecuteMsg::SetAllocations {
+            allocations: vec![
+                PotAllocation {
+                    name: "emergency".to_string(),
+                    allocation: 5000,
+                },
+                PotAllocation {
+                    name: "vacation".to_string(),
+                    allocation: 3333,
+                },
+            ],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // saved funds are spread, the default pot takes the rest
+        let info = mock_info(OWNER, &coins(1000, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = query_pots(deps.as_ref(), OWNER.to_string()).unwrap();
+        let balances: Vec<(String, Vec<Coin>)> = res
+            .pots
+            .into_iter()
+            .map(|pot| (pot.name, pot.balance))
+            .collect();
+        assert_eq!(
+            balances,
+            vec![
+                (DEFAULT_POT.to_string(), coins(167, "UST")),
+                ("emergency".to_string(), coins(500, "UST")),
+                ("vacation".to_string(), coins(333, "UST")),
+            ]
+        );
+
+        // move, rename and close keep every coin in the account
+        let msg = ExecuteMsg::MovePotFunds {
+            from: "emergency".to_string(),
+            to: "vacation".to_string(),
+            amount: coin(100, "UST"),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::RenamePot {
+            name: "vacation".to_string(),
+            new_name: "house".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::ClosePot {
+            name: "emergency".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::ClosePot {
+            name: DEFAULT_POT.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::DefaultPot {});
+
+        let res = query_pots(deps.as_ref(), OWNER.to_string()).unwrap();
+        let balances: Vec<(String, Vec<Coin>)> = res
+            .pots
+            .into_iter()
+            .map(|pot| (pot.name, pot.balance))
+            .collect();
+        assert_eq!(
+            balances,
+            vec![
+                (DEFAULT_POT.to_string(), coins(567, "UST")),
+                ("house".to_string(), coins(433, "UST")),
+            ]
+        );
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert_eq!(res.balance, coins(1000, "UST"));
+
+        // flush empties every pot
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap();
+        let res = query_pots(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert!(res.pots.iter().all(|pot| pot.balance.is_empty()));
+    }
+
+    #[test]
+    fn try_waterfall() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 5000,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        for (name, target) in [("emergency", 300), ("vacation", 200)] {
+            let msg = ExecuteMsg::CreatePot {
+                name: name.to_string(),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+            let msg = ExecuteMsg::SetPotTarget {
+                name: name.to_string(),
+                target: Some(coin(target, "UST")),
+            };
+            execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        }
+
+        // every pot in the order must exist
+        let msg = ExecuteMsg::SetAllocationMode {
+            mode: AllocationMode::Waterfall {
+                order: vec!["emergency".to_string(), "house".to_string()],
+                overflow: Overflow::Owner,
+            },
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::PotNotFound {
+                name: "house".to_string()
+            }
+        );
+        let msg = ExecuteMsg::SetAllocationMode {
+            mode: AllocationMode::Waterfall {
+                order: vec!["emergency".to_string(), "vacation".to_string()],
+                overflow: Overflow::Owner,
+            },
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // the emergency fund fills first
+        let info = mock_info(OWNER, &coins(800, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // the next deposit tops up vacation and the rest goes back
+        let res =
+            query_simulate_transfer(deps.as_ref(), OWNER.to_string(), coins(600, "UST"), None)
+                .unwrap();
+        assert_eq!(res.payout, coins(300, "UST"));
+        assert_eq!(res.returned, coins(200, "UST"));
+        assert_eq!(
+            res.pots,
+            vec![
+                PotShare {
+                    name: DEFAULT_POT.to_string(),
+                    amount: vec![],
+                },
+                PotShare {
+                    name: "emergency".to_string(),
+                    amount: vec![],
+                },
+                PotShare {
+                    name: "vacation".to_string(),
+                    amount: coins(100, "UST"),
+                },
+            ]
+        );
+
+        let info = mock_info(OWNER, &coins(600, "UST"));
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: coins(300, "UST"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: coins(200, "UST"),
+                }),
+            ]
+        );
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert_eq!(res.balance, coins(500, "UST"));
+        let res = query_pots(deps.as_ref(), OWNER.to_string()).unwrap();
+        let balances: Vec<Vec<Coin>> = res.pots.into_iter().map(|pot| pot.balance).collect();
+        assert_eq!(balances, vec![vec![], coins(300, "UST"), coins(200, "UST")]);
+    }
+
+    #[test]
+    fn try_withdrawal_delay() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 10000,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &coins(1000, "UST")),
+            msg,
+        )
+        .unwrap();
+        // delays that would overflow the block time are refused
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: None,
+            rounding: None,
+            withdrawal_delay: Some(u64::MAX),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::PeriodTooLong { max: MAX_PERIOD });
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: None,
+            rounding: None,
+            withdrawal_delay: Some(72 * 60 * 60),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // no more instant flush
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WithdrawalDelayed {
+                delay: 72 * 60 * 60
+            }
+        );
+
+        // requested funds can't be requested twice
+        let msg = ExecuteMsg::RequestWithdrawal {
+            amount: Uint128::new(600),
+            denom: "UST".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::RequestWithdrawal {
+            amount: Uint128::new(500),
+            denom: "UST".to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InsufficientFunds {});
+        let msg = ExecuteMsg::RequestWithdrawal {
+            amount: Uint128::new(400),
+            denom: "UST".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        let res = query_withdrawals(deps.as_ref(), OWNER.to_string()).unwrap();
+        let env = mock_env();
+        assert_eq!(
+            res.withdrawals,
+            vec![
+                WithdrawalRequest {
+                    id: 1,
+                    amount: coin(600, "UST"),
+                    requested_at: env.block.time,
+                    available_at: env.block.time.plus_seconds(72 * 60 * 60),
+                },
+                WithdrawalRequest {
+                    id: 2,
+                    amount: coin(400, "UST"),
+                    requested_at: env.block.time,
+                    available_at: env.block.time.plus_seconds(72 * 60 * 60),
+                },
+            ]
+        );
+
+        // the delay has to pass
+        let msg = ExecuteMsg::ExecuteWithdrawal { id: 1 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WithdrawalNotReady {
+                available_at: env.block.time.plus_seconds(72 * 60 * 60)
+            }
+        );
+
+        // cancelled funds stay saved
+        let msg = ExecuteMsg::CancelWithdrawal { id: 2 };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::ExecuteWithdrawal { id: 2 };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::WithdrawalNotFound { id: 2 });
+
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(72 * 60 * 60);
+        let msg = ExecuteMsg::ExecuteWithdrawal { id: 1 };
+        let res = execute(deps.as_mut(), later, mock_info(OWNER, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(600, "UST"),
+            })]
+        );
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert_eq!(res.balance, coins(400, "UST"));
+        let res = query_withdrawals(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert!(res.withdrawals.is_empty());
+
+        // dropping the delay waits out the current one
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: None,
+            rounding: None,
+            withdrawal_delay: Some(0),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WithdrawalDelayed {
+                delay: 72 * 60 * 60
+            }
+        );
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(72 * 60 * 60);
+        execute(
+            deps.as_mut(),
+            later,
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn try_inheritance() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 10000,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &coins(1000, "UST")),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::UpdateInheritance {
+            inheritance: Some(InheritanceMsg {
+                beneficiary: "heir".to_string(),
+                inactivity_period: u64::MAX,
+            }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::PeriodTooLong { max: MAX_PERIOD });
+        let msg = ExecuteMsg::UpdateInheritance {
+            inheritance: Some(InheritanceMsg {
+                beneficiary: "heir".to_string(),
+                inactivity_period: 1000,
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let at = |seconds: u64| {
+            let mut env = mock_env();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+
+        // only the beneficiary can claim, and only after the period
+        let msg = ExecuteMsg::ClaimInheritance {
+            owner: OWNER.to_string(),
+        };
+        let err = execute(deps.as_mut(), at(2000), mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let msg = ExecuteMsg::ClaimInheritance {
+            owner: OWNER.to_string(),
+        };
+        let err = execute(deps.as_mut(), at(500), mock_info("heir", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InheritanceNotClaimable {
+                claimable_at: at(1000).block.time
+            }
+        );
+
+        // any owner action resets the timer
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: None,
+            rounding: None,
+            withdrawal_delay: None,
+        };
+        execute(deps.as_mut(), at(800), mock_info(OWNER, &[]), msg).unwrap();
+        let res = query_inheritance(deps.as_ref(), at(1000), OWNER.to_string()).unwrap();
+        assert_eq!(res.beneficiary, Some(Addr::unchecked("heir")));
+        assert_eq!(res.claimable_at, Some(at(1800).block.time));
+        assert_eq!(res.remaining_seconds, Some(800));
+        let msg = ExecuteMsg::ClaimInheritance {
+            owner: OWNER.to_string(),
+        };
+        let err = execute(deps.as_mut(), at(1000), mock_info("heir", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InheritanceNotClaimable {
+                claimable_at: at(1800).block.time
+            }
+        );
+
+        let msg = ExecuteMsg::ClaimInheritance {
+            owner: OWNER.to_string(),
+        };
+        let res = execute(deps.as_mut(), at(1800), mock_info("heir", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "heir".to_string(),
+                amount: coins(1000, "UST"),
+            })]
+        );
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert!(res.balance.is_empty());
+    }
+
+    #[test]
+    fn try_early_withdraw() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let lock = LockPolicy::AtHeight(env.block.height + 100);
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 10000,
+                rounding: None,
+                lock: Some(lock.clone()),
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        let transfer = |deps: DepsMut, saver: &str, amount: u128| {
+            let msg = ExecuteMsg::Transfer {
+                received_funds: None,
+                savings_rate: None,
+            };
+            execute(
+                deps,
+                mock_env(),
+                mock_info(saver, &coins(amount, "UST")),
+                msg,
+            )
+            .unwrap();
+        };
+        let early_withdraw = |deps: DepsMut, saver: &str| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info(saver, &[]),
+                ExecuteMsg::EarlyWithdraw {},
+            )
+        };
+        let update_penalty = |deps: DepsMut, saver: &str, rate: u16, recipient: Option<&str>| {
+            let msg = ExecuteMsg::UpdatePenalty {
+                penalty: Some(PenaltyMsg {
+                    rate,
+                    recipient: recipient.map(|recipient| recipient.to_string()),
+                }),
+            };
+            execute(deps, mock_env(), mock_info(saver, &[]), msg)
+        };
+        transfer(deps.as_mut(), OWNER, 1000);
+
+        // no penalty, no way out, and none can be added while locked
+        let err = early_withdraw(deps.as_mut(), OWNER).unwrap_err();
+        assert_eq!(err, ContractError::EarlyWithdrawDisabled {});
+        let err = update_penalty(deps.as_mut(), OWNER, 1, Some("charity")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StillLocked {
+                unlocks_at: lock.clone()
+            }
+        );
+
+        // the penalty comes with the locked account
+        let open = |deps: DepsMut, saver: &str, recipient: Option<&str>| {
+            let msg = ExecuteMsg::OpenAccount {
+                savings_rate: 10000,
+                rounding: None,
+                lock: Some(lock.clone()),
+                goal: None,
+                split: None,
+                penalty: Some(PenaltyMsg {
+                    rate: 1000,
+                    recipient: recipient.map(|recipient| recipient.to_string()),
+                }),
+            };
+            execute(deps, mock_env(), mock_info(saver, &[]), msg)
+        };
+        let err = open(deps.as_mut(), "locked", Some("locked")).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPenalty {});
+        open(deps.as_mut(), "locked", Some("charity")).unwrap();
+        transfer(deps.as_mut(), "locked", 999);
+
+        // it can't be lowered or redirected while locked, only raised
+        let err = update_penalty(deps.as_mut(), "locked", 500, Some("charity")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StillLocked {
+                unlocks_at: lock.clone()
+            }
+        );
+        let err = update_penalty(deps.as_mut(), "locked", 1000, Some("friend")).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StillLocked {
+                unlocks_at: lock.clone()
+            }
+        );
+        let msg = ExecuteMsg::UpdatePenalty { penalty: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("locked", &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::StillLocked {
+                unlocks_at: lock.clone()
+            }
+        );
+        update_penalty(deps.as_mut(), "locked", 1000, Some("charity")).unwrap();
+
+        // the withheld part is rounded up
+        let res = early_withdraw(deps.as_mut(), "locked").unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "locked".to_string(),
+                    amount: coins(899, "UST"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "charity".to_string(),
+                    amount: coins(100, "UST"),
+                }),
+            ]
+        );
+        assert_eq!(("penalty", "100UST"), res.attributes[1]);
+
+        // without a recipient the penalty is burned
+        transfer(deps.as_mut(), "locked", 1000);
+        update_penalty(deps.as_mut(), "locked", 2000, Some("charity")).unwrap();
+        open(deps.as_mut(), "burner", None).unwrap();
+        transfer(deps.as_mut(), "burner", 1000);
+        let res = early_withdraw(deps.as_mut(), "burner").unwrap();
+        assert_eq!(
+            res.messages[1],
+            SubMsg::new(BankMsg::Burn {
+                amount: coins(100, "UST"),
+            })
+        );
+
+        let res = early_withdraw(deps.as_mut(), "locked").unwrap();
+        assert_eq!(("penalty", "200UST"), res.attributes[1]);
+        let res = query_stats(deps.as_ref(), "locked".to_string()).unwrap();
+        assert_eq!(res.stats[0].total_withdrawn, Uint128::new(1999));
+        assert_eq!(res.stats[0].total_penalty, Uint128::new(300));
+    }
+
+    #[test]
+    fn try_guardian() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 10000,
+                rounding: None,
+                lock: Some(LockPolicy::AtHeight(env.block.height + 100)),
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &coins(1000, "UST")),
+            msg,
+        )
+        .unwrap();
+
+        // the first guardian is set right away
+        let msg = ExecuteMsg::ProposeGuardian {
+            guardian: Some("friend".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // while locked, early withdrawals wait for the guardian
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::EarlyWithdraw {},
+        )
+        .unwrap();
+        assert_eq!(0, res.messages.len());
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::EarlyWithdraw {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::GuardianRequestPending {});
+        let msg = ExecuteMsg::Approve {
+            owner: OWNER.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let msg = ExecuteMsg::Reject {
+            owner: OWNER.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("friend", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Approve {
+            owner: OWNER.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("friend", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoGuardianRequest {});
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::EarlyWithdraw {},
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Approve {
+            owner: OWNER.to_string(),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("friend", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(1000, "UST"),
+            })]
+        );
+
+        // a friendlier guardian has to wait out the delay
+        let msg = ExecuteMsg::ProposeGuardian {
+            guardian: Some("pushover".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let effective_at = env.block.time.plus_seconds(GUARDIAN_CHANGE_DELAY);
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::ConfirmGuardian {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::GuardianChangeNotReady { effective_at });
+        let res = query_guardian(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert_eq!(res.guardian, Some(Addr::unchecked("friend")));
+
+        let mut later = mock_env();
+        later.block.time = effective_at;
+        execute(
+            deps.as_mut(),
+            later,
+            mock_info(OWNER, &[]),
+            ExecuteMsg::ConfirmGuardian {},
+        )
+        .unwrap();
+        let res = query_guardian(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert_eq!(
+            res,
+            GuardianResponse {
+                guardian: Some(Addr::unchecked("pushover")),
+                pending_change: None,
+                pending_request: None,
+            }
+        );
+
+        // once unlocked, early withdrawals wait out the withdrawal delay like a flush
+        let mut unlocked = mock_env();
+        unlocked.block.height += 100;
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: None,
+            rounding: None,
+            withdrawal_delay: Some(72 * 60 * 60),
+        };
+        execute(deps.as_mut(), unlocked.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            unlocked.clone(),
+            mock_info(OWNER, &coins(1000, "UST")),
+            msg,
+        )
+        .unwrap();
+        for msg in [ExecuteMsg::Flush {}, ExecuteMsg::EarlyWithdraw {}] {
+            let err =
+                execute(deps.as_mut(), unlocked.clone(), mock_info(OWNER, &[]), msg).unwrap_err();
+            assert_eq!(
+                err,
+                ContractError::WithdrawalDelayed {
+                    delay: 72 * 60 * 60
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn try_approvers() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 10000,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        let transfer = |deps: DepsMut, amount: u128| {
+            let msg = ExecuteMsg::Transfer {
+                received_funds: None,
+                savings_rate: None,
+            };
+            execute(
+                deps,
+                mock_env(),
+                mock_info(OWNER, &coins(amount, "UST")),
+                msg,
+            )
+            .unwrap();
+        };
+        let flush = |deps: DepsMut| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info(OWNER, &[]),
+                ExecuteMsg::Flush {},
+            )
+            .unwrap()
+        };
+        let msg = ExecuteMsg::UpdateApprovers {
+            approvers: Some(ApproverSetMsg {
+                approvers: vec!["alice".to_string(), "bob".to_string(), "carol".to_string()],
+                required: 2,
+                threshold: coins(500, "UST"),
+                expires_in: u64::MAX,
+            }),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::PeriodTooLong { max: MAX_PERIOD });
+        let msg = ExecuteMsg::UpdateApprovers {
+            approvers: Some(ApproverSetMsg {
+                approvers: vec!["alice".to_string(), "bob".to_string(), "carol".to_string()],
+                required: 2,
+                threshold: coins(500, "UST"),
+                expires_in: 1000,
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // small withdrawals go through right away
+        transfer(deps.as_mut(), 100);
+        let res = flush(deps.as_mut());
+        assert_eq!(1, res.messages.len());
+
+        // large ones become a proposal
+        transfer(deps.as_mut(), 1000);
+        let res = flush(deps.as_mut());
+        assert_eq!(0, res.messages.len());
+        let res =
+            query_proposals(deps.as_ref(), mock_env(), OWNER.to_string(), None, None).unwrap();
+        assert_eq!(res.proposals.len(), 1);
+        assert_eq!(res.proposals[0].amount, coins(1000, "UST"));
+
+        let approve = |deps: DepsMut, env: Env, approver: &str, id: u64| {
+            let msg = ExecuteMsg::ApproveProposal { id };
+            execute(deps, env, mock_info(approver, &[]), msg)
+        };
+        let err = approve(deps.as_mut(), mock_env(), OWNER, 1).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = approve(deps.as_mut(), mock_env(), "alice", 1).unwrap();
+        assert_eq!(0, res.messages.len());
+        let err = approve(deps.as_mut(), mock_env(), "alice", 1).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyApproved {});
+
+        // a revoked approval no longer counts
+        let msg = ExecuteMsg::RevokeApproval { id: 1 };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        let res = approve(deps.as_mut(), mock_env(), "bob", 1).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // expired proposals can't be approved and aren't listed
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(1000);
+        let err = approve(deps.as_mut(), later.clone(), "carol", 1).unwrap_err();
+        assert_eq!(err, ContractError::ProposalExpired { id: 1 });
+        let res = query_proposals(deps.as_ref(), later, OWNER.to_string(), None, None).unwrap();
+        assert!(res.proposals.is_empty());
+
+        flush(deps.as_mut());
+        approve(deps.as_mut(), mock_env(), "alice", 2).unwrap();
+        let res = approve(deps.as_mut(), mock_env(), "carol", 2).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(1000, "UST"),
+            })]
+        );
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert!(res.balance.is_empty());
+
+        // removing the approvers waits out the delay, large flushes stay gated meanwhile
+        let msg = ExecuteMsg::UpdateApprovers { approvers: None };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let res = query_approvers(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert!(res.approvers.is_some());
+        assert_eq!(
+            res.pending_change,
+            Some(ApproverChange {
+                approvers: None,
+                effective_at: mock_env().block.time.plus_seconds(APPROVERS_CHANGE_DELAY),
+            })
+        );
+        transfer(deps.as_mut(), 1000);
+        let res = flush(deps.as_mut());
+        assert_eq!(0, res.messages.len());
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::ConfirmApprovers {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ApproversChangeNotReady {
+                effective_at: mock_env().block.time.plus_seconds(APPROVERS_CHANGE_DELAY),
+            }
+        );
+
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(APPROVERS_CHANGE_DELAY);
+        let msg = ExecuteMsg::ConfirmApprovers {};
+        execute(deps.as_mut(), later.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        let res = query_approvers(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert_eq!(
+            res,
+            ApproversResponse {
+                approvers: None,
+                pending_change: None,
+            }
+        );
+        let res = execute(
+            deps.as_mut(),
+            later,
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap();
+        assert_eq!(1, res.messages.len());
+    }
+
+    #[test]
+    fn try_approved_payouts() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 10000,
+                rounding: None,
+                lock: None,
+                goal: Some(coin(1000, "UST")),
+                split: None,
+            },
+        )
+        .unwrap();
+        let msg = ExecuteMsg::UpdateApprovers {
+            approvers: Some(ApproverSetMsg {
+                approvers: vec!["alice".to_string()],
+                required: 1,
+                threshold: coins(500, "UST"),
+                expires_in: 1000,
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // a reached goal over the threshold stays saved
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &coins(1000, "UST")),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // requests without a delay still wait for the approvers
+        let msg = ExecuteMsg::RequestWithdrawal {
+            amount: Uint128::new(600),
+            denom: "UST".to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::ExecuteWithdrawal { id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        let res =
+            query_proposals(deps.as_ref(), mock_env(), OWNER.to_string(), None, None).unwrap();
+        assert_eq!(res.proposals[0].kind, ProposalKind::Withdrawal { id: 1 });
+        let msg = ExecuteMsg::ApproveProposal { id: 1 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(600, "UST"),
+            })]
+        );
+        let res = query_withdrawals(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert!(res.withdrawals.is_empty());
+
+        // so do early withdrawals, which still withhold the penalty
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &coins(600, "UST")),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::UpdatePenalty {
+            penalty: Some(PenaltyMsg {
+                rate: 1000,
+                recipient: Some("charity".to_string()),
+            }),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::EarlyWithdraw {};
+        let res = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        let msg = ExecuteMsg::ApproveProposal { id: 2 };
+        let res = execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: coins(900, "UST"),
+                }),
+                SubMsg::new(BankMsg::Send {
+                    to_address: "charity".to_string(),
+                    amount: coins(100, "UST"),
+                }),
+            ]
+        );
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert!(res.balance.is_empty());
+
+        // splitting a withdrawal counts every part against the day's threshold
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(APPROVAL_WINDOW);
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info(OWNER, &coins(600, "UST")),
+            msg,
+        )
+        .unwrap();
+        for _ in 0..2 {
+            let msg = ExecuteMsg::RequestWithdrawal {
+                amount: Uint128::new(300),
+                denom: "UST".to_string(),
+            };
+            execute(deps.as_mut(), later.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ExecuteWithdrawal { id: 2 };
+        let res = execute(deps.as_mut(), later.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        let msg = ExecuteMsg::ExecuteWithdrawal { id: 3 };
+        let res = execute(deps.as_mut(), later.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        assert_eq!(("action", "propose_withdrawal"), res.attributes[0]);
+
+        // an inheritance claim is dropped once the saver shows up again
+        let msg = ExecuteMsg::UpdateInheritance {
+            inheritance: Some(InheritanceMsg {
+                beneficiary: "heir".to_string(),
+                inactivity_period: 1000,
+            }),
+        };
+        execute(deps.as_mut(), later.clone(), mock_info(OWNER, &[]), msg).unwrap();
+        let at = |seconds: u64| {
+            let mut env = later.clone();
+            env.block.time = env.block.time.plus_seconds(seconds);
+            env
+        };
+        let msg = ExecuteMsg::ClaimInheritance {
+            owner: OWNER.to_string(),
+        };
+        let res = execute(deps.as_mut(), at(1000), mock_info("heir", &[]), msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        let msg = ExecuteMsg::UpdateConfig {
+            savings_rate: None,
+            rounding: None,
+            withdrawal_delay: None,
+        };
+        execute(deps.as_mut(), at(1500), mock_info(OWNER, &[]), msg).unwrap();
+        let msg = ExecuteMsg::ApproveProposal { id: 4 };
+        let err = execute(deps.as_mut(), at(1500), mock_info("alice", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::ProposalNotFound { id: 4 });
+
+        // otherwise it pays the beneficiary once approved
+        let msg = ExecuteMsg::ClaimInheritance {
+            owner: OWNER.to_string(),
+        };
+        execute(deps.as_mut(), at(2500), mock_info("heir", &[]), msg).unwrap();
+        let msg = ExecuteMsg::ApproveProposal { id: 5 };
+        let res = execute(deps.as_mut(), at(2500), mock_info("alice", &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "heir".to_string(),
+                amount: coins(300, "UST"),
+            })]
+        );
+    }
+
+    #[test]
+    fn try_pause() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 10000,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        let transfer = |deps: DepsMut| {
+            let msg = ExecuteMsg::Transfer {
+                received_funds: None,
+                savings_rate: None,
+            };
+            execute(deps, mock_env(), mock_info(OWNER, &coins(100, "UST")), msg)
+        };
+        let flush = |deps: DepsMut| {
+            execute(
+                deps,
+                mock_env(),
+                mock_info(OWNER, &[]),
+                ExecuteMsg::Flush {},
+            )
+        };
+        let msg = ExecuteMsg::SetPauseGuardian {
+            guardian: Some("watchman".to_string()),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+
+        // only the owner and the pause guardian can pause
+        let msg = ExecuteMsg::Pause { scope: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // withdrawals alone can be paused, reads keep working
+        transfer(deps.as_mut()).unwrap();
+        let msg = ExecuteMsg::Pause {
+            scope: Some(PauseScope::Withdrawals),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("watchman", &[]), msg).unwrap();
+        assert_eq!(flush(deps.as_mut()).unwrap_err(), ContractError::Paused {});
+        let msg = ExecuteMsg::Unstake {
+            address: OWNER.to_string(),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info("anyone", &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            ExecuteMsg::Compound {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let msg = ExecuteMsg::Invest {
+            amount: coin(100, "UST"),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let msg = ExecuteMsg::Divest {
+            strategy: "market".to_string(),
+            shares: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let msg = ExecuteMsg::Swap {
+            amount: coin(100, "UST"),
+            ask_denom: "uosmo".to_string(),
+            belief_price: Decimal::percent(50),
+            max_slippage: Decimal::percent(1),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        transfer(deps.as_mut()).unwrap();
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert_eq!(res.balance, coins(200, "UST"));
+        let res = query_pause(deps.as_ref()).unwrap();
+        assert_eq!(
+            res,
+            PauseResponse {
+                pause_guardian: Some(Addr::unchecked("watchman")),
+                deposits: false,
+                withdrawals: true,
+                paused_by: Some(Addr::unchecked("watchman")),
+                paused_at: Some(mock_env().block.time),
+            }
+        );
+
+        let msg = ExecuteMsg::Pause { scope: None };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        assert_eq!(
+            transfer(deps.as_mut()).unwrap_err(),
+            ContractError::Paused {}
+        );
+
+        let msg = ExecuteMsg::Unpause {
+            scope: Some(PauseScope::Deposits),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        transfer(deps.as_mut()).unwrap();
+        assert_eq!(flush(deps.as_mut()).unwrap_err(), ContractError::Paused {});
+
+        let msg = ExecuteMsg::Unpause { scope: None };
+        execute(deps.as_mut(), mock_env(), mock_info("watchman", &[]), msg).unwrap();
+        flush(deps.as_mut()).unwrap();
+        let res = query_pause(deps.as_ref()).unwrap();
+        assert!(!res.deposits && !res.withdrawals);
+    }
+
+    #[test]
+    fn try_cw20() {
+        let mut deps = mock_dependencies();
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            InstantiateMsg {
+                owner: OWNER.to_string(),
+                savings_rate: 5000,
+                rounding: None,
+                lock: None,
+                goal: None,
+                split: None,
+            },
+        )
+        .unwrap();
+        let cw20_transfer = |recipient: &str, amount: u128| {
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: "token".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount: Uint128::new(amount),
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        };
+
+        let receive = |sender: &str| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: sender.to_string(),
+                amount: Uint128::new(1000),
+                msg: to_binary(&ReceiveMsg::Transfer { savings_rate: None }).unwrap(),
+            })
+        };
+
+        // only tokens the owner accepted can be sent
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("token", &[]),
+            receive(OWNER),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TokenNotAccepted {
+                token: "token".to_string()
+            }
+        );
+        let msg = ExecuteMsg::UpdateToken {
+            token: "token".to_string(),
+            accepted: true,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        let res = query_token(deps.as_ref(), "token".to_string()).unwrap();
+        assert!(res.accepted);
+
+        // only account holders can send tokens
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("token", &[]),
+            receive("anyone"),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::AccountNotFound {});
+
+        // the spending portion goes back as a token transfer, and the deposit counts as activity
+        let mut later = mock_env();
+        later.block.time = later.block.time.plus_seconds(100);
+        let res = execute(
+            deps.as_mut(),
+            later.clone(),
+            mock_info("token", &[]),
+            receive(OWNER),
+        )
+        .unwrap();
+        assert_eq!(res.messages, vec![cw20_transfer(OWNER, 500)]);
+        let account = ACCOUNTS
+            .load(&deps.storage, &Addr::unchecked(OWNER))
+            .unwrap();
+        assert_eq!(account.last_activity, later.block.time);
+
+        // native denoms can't pose as tokens
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &coins(100, "cw20:token")),
+            msg,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidDenom {
+                denom: "cw20:token".to_string()
+            }
+        );
+
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &coins(100, "UST")),
+            msg,
+        )
+        .unwrap();
+        let res = query_balance(deps.as_ref(), mock_env(), OWNER.to_string()).unwrap();
+        assert_eq!(res.balance, vec![coin(500, "cw20:token"), coin(50, "UST")]);
+
+        // flush pays coins and tokens alike
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::Flush {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: OWNER.to_string(),
+                    amount: coins(50, "UST"),
+                }),
+                cw20_transfer(OWNER, 500),
+            ]
+        );
+    }
+
+    // Chain with a single validator, OWNER holds 1000ustake and its savings
+    // contract locks them until unlocks_at
+    fn mock_staking_app(unlocks_in: u64) -> (App, Addr, Timestamp) {
+        let owner = Addr::unchecked(OWNER);
+        let mut app = App::new(|router, api, storage| {
+            router
+                .bank
+                .init_balance(storage, &owner, coins(1000, "ustake"))
+                .unwrap();
+            router
+                .staking
+                .setup(
+                    storage,
+                    StakingInfo {
+                        bonded_denom: "ustake".to_string(),
+                        unbonding_time: 100,
+                        apr: Decimal::percent(10),
+                    },
+                )
+                .unwrap();
+            router
+                .staking
+                .add_validator(
+                    api,
+                    storage,
+                    &mock_env().block,
+                    Validator {
+                        address: "validator".to_string(),
+                        commission: Decimal::percent(5),
+                        max_commission: Decimal::percent(10),
+                        max_change_rate: Decimal::percent(1),
+                    },
+                )
+                .unwrap();
+        });
+        let code_id = app.store_code(Box::new(
+            ContractWrapper::new(execute, instantiate, query).with_reply(reply),
+        ));
+        let unlocks_at = app.block_info().time.plus_seconds(unlocks_in);
+        let contract = app
+            .instantiate_contract(
+                code_id,
+                owner.clone(),
+                &InstantiateMsg {
+                    owner: OWNER.to_string(),
+                    savings_rate: 5000,
+                    rounding: None,
+                    lock: Some(LockPolicy::AtTime(unlocks_at)),
+                    goal: None,
+                    split: None,
+                },
+                &[],
+                "savings",
+                None,
+            )
+            .unwrap();
+        (app, contract, unlocks_at)
+    }
+
+    #[test]
+    fn try_staking() {
+        let owner = Addr::unchecked(OWNER);
+        let keeper = Addr::unchecked("keeper");
+        let (mut app, contract, unlocks_at) = mock_staking_app(1000);
+
+        // only the owner chooses the validator, and it has to exist
+        let msg = ExecuteMsg::UpdateStaking {
+            validator: Some("validator".to_string()),
+            unbonding_period: 100,
+        };
+        app.execute_contract(keeper.clone(), contract.clone(), &msg, &[])
+            .unwrap_err();
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                contract.clone(),
+                &ExecuteMsg::UpdateStaking {
+                    validator: Some("unknown".to_string()),
+                    unbonding_period: 100,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InvalidValidator {
+                validator: "unknown".to_string()
+            }
+            .to_string()
+        );
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                contract.clone(),
+                &ExecuteMsg::UpdateStaking {
+                    validator: Some("validator".to_string()),
+                    unbonding_period: u64::MAX,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::PeriodTooLong { max: MAX_PERIOD }.to_string()
+        );
+        app.execute_contract(owner.clone(), contract.clone(), &msg, &[])
+            .unwrap();
+
+        // locked savings are delegated as they come in
+        let msg = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &msg,
+            &coins(1000, "ustake"),
+        )
+        .unwrap();
+        let delegation = app
+            .wrap()
+            .query_delegation(&contract, "validator")
+            .unwrap()
+            .unwrap();
+        assert_eq!(delegation.amount, coin(500, "ustake"));
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract,
+                &QueryMsg::GetBalance {
+                    address: OWNER.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, coins(500, "ustake"));
+        assert_eq!(balance.liquid, vec![]);
+        assert_eq!(balance.delegated, coins(500, "ustake"));
+
+        // the unbonding period can't be shortened while savings are delegated
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                contract.clone(),
+                &ExecuteMsg::UpdateStaking {
+                    validator: Some("validator".to_string()),
+                    unbonding_period: 10,
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::SavingsStaked {}.to_string()
+        );
+
+        // a slash is taken out of the savings when they are unstaked
+        app.sudo(SudoMsg::Staking(StakingSudo::Slash {
+            validator: "validator".to_string(),
+            percentage: Decimal::percent(10),
+        }))
+        .unwrap();
+
+        // the keeper has to wait until the unbonding would finish by the unlock
+        let err = app
+            .execute_contract(
+                keeper.clone(),
+                contract.clone(),
+                &ExecuteMsg::Unstake {
+                    address: OWNER.to_string(),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::UnstakeNotDue {
+                due_at: unlocks_at.minus_seconds(100)
+            }
+            .to_string()
+        );
+        app.update_block(|block| block.time = unlocks_at.minus_seconds(100));
+        app.execute_contract(
+            keeper.clone(),
+            contract.clone(),
+            &ExecuteMsg::Unstake {
+                address: OWNER.to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract,
+                &QueryMsg::GetBalance {
+                    address: OWNER.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, coins(450, "ustake"));
+        assert_eq!(balance.delegated, vec![]);
+        assert_eq!(balance.unbonding, coins(450, "ustake"));
+
+        // unbonded savings are paid out once unlocked
+        app.update_block(|block| block.time = unlocks_at);
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {}))
+            .unwrap();
+        app.execute_contract(owner.clone(), contract.clone(), &ExecuteMsg::Flush {}, &[])
+            .unwrap();
+        let balance = app.wrap().query_balance(&owner, "ustake").unwrap();
+        assert_eq!(balance, coin(950, "ustake"));
+    }
+
+    #[test]
+    fn try_compound() {
+        let owner = Addr::unchecked(OWNER);
+        let other = Addr::unchecked("other");
+        let (mut app, contract, unlocks_at) = mock_staking_app(2 * 365 * 24 * 60 * 60);
+        app.send_tokens(owner.clone(), other.clone(), &coins(600, "ustake"))
+            .unwrap();
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &ExecuteMsg::UpdateStaking {
+                validator: Some("validator".to_string()),
+                unbonding_period: 100,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // nothing to compound before anything is delegated
+        let err = app
+            .execute_contract(
+                other.clone(),
+                contract.clone(),
+                &ExecuteMsg::Compound {},
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::NotStaked {}.to_string()
+        );
+
+        // OWNER delegates 200, other 600
+        let transfer = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &transfer,
+            &coins(400, "ustake"),
+        )
+        .unwrap();
+        app.execute_contract(
+            other.clone(),
+            contract.clone(),
+            &ExecuteMsg::OpenAccount {
+                savings_rate: 10000,
+                rounding: None,
+                lock: Some(LockPolicy::AtTime(unlocks_at)),
+                goal: None,
+                split: None,
+                penalty: None,
+            },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            other.clone(),
+            contract.clone(),
+            &transfer,
+            &coins(600, "ustake"),
+        )
+        .unwrap();
+
+        // a year at 10% minus 5% commission, shared by delegated amount
+        app.update_block(|block| block.time = block.time.plus_seconds(365 * 24 * 60 * 60));
+        app.execute_contract(
+            Addr::unchecked("anyone"),
+            contract.clone(),
+            &ExecuteMsg::Compound {},
+            &[],
+        )
+        .unwrap();
+        let delegation = app
+            .wrap()
+            .query_delegation(&contract, "validator")
+            .unwrap()
+            .unwrap();
+        assert_eq!(delegation.amount, coin(876, "ustake"));
+
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract,
+                &QueryMsg::GetBalance {
+                    address: OWNER.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, coins(219, "ustake"));
+        assert_eq!(balance.delegated, coins(219, "ustake"));
+        let stats: StatsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract,
+                &QueryMsg::Stats {
+                    address: "other".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(stats.stats[0].total_saved, Uint128::new(600));
+        assert_eq!(stats.stats[0].total_compounded, Uint128::new(57));
+    }
+
+    #[test]
+    fn try_strategy() {
+        let owner = Addr::unchecked(OWNER);
+        let (mut app, contract, _) = mock_staking_app(1000);
+        let code_id = app.store_code(mock_money_market());
+        let market = app
+            .instantiate_contract(
+                code_id,
+                owner.clone(),
+                &MoneyMarketInstantiateMsg {
+                    denom: "ustake".to_string(),
+                },
+                &[],
+                "market",
+                None,
+            )
+            .unwrap();
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &ExecuteMsg::Transfer {
+                received_funds: None,
+                savings_rate: None,
+            },
+            &coins(400, "ustake"),
+        )
+        .unwrap();
+
+        // nothing to invest in until the owner picks a strategy
+        let invest = ExecuteMsg::Invest {
+            amount: coin(150, "ustake"),
+        };
+        let err = app
+            .execute_contract(owner.clone(), contract.clone(), &invest, &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::NoStrategy {}.to_string()
+        );
+        let err = app
+            .execute_contract(
+                owner.clone(),
+                contract.clone(),
+                &ExecuteMsg::UpdateStrategy {
+                    strategy: Some("keeper".to_string()),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InvalidStrategy {
+                strategy: "keeper".to_string()
+            }
+            .to_string()
+        );
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &ExecuteMsg::UpdateStrategy {
+                strategy: Some(market.to_string()),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // only the strategy's own denom can be invested
+        for denom in ["cw20:token", "uosmo"] {
+            let err = app
+                .execute_contract(
+                    owner.clone(),
+                    contract.clone(),
+                    &ExecuteMsg::Invest {
+                        amount: coin(150, denom),
+                    },
+                    &[],
+                )
+                .unwrap_err();
+            let expected = match denom {
+                "uosmo" => ContractError::StrategyDenomMismatch {
+                    expected: "ustake".to_string(),
+                    received: denom.to_string(),
+                },
+                _ => ContractError::InvalidDenom {
+                    denom: denom.to_string(),
+                },
+            };
+            assert_eq!(err.root_cause().to_string(), expected.to_string());
+        }
+
+        // invested savings leave the balance for shares
+        app.execute_contract(owner.clone(), contract.clone(), &invest, &[])
+            .unwrap();
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract,
+                &QueryMsg::GetBalance {
+                    address: OWNER.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, coins(50, "ustake"));
+
+        // interest shows up in the value of the shares
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: market.to_string(),
+            amount: coins(30, "ustake"),
+        }))
+        .unwrap();
+        let query = QueryMsg::Investments {
+            address: OWNER.to_string(),
+        };
+        let res: InvestmentsResponse = app.wrap().query_wasm_smart(&contract, &query).unwrap();
+        assert_eq!(
+            res.investments,
+            vec![Investment {
+                strategy: market.clone(),
+                shares: Uint128::new(150),
+                value: coin(180, "ustake"),
+            }]
+        );
+
+        // divesting brings principal and interest back into the savings
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &ExecuteMsg::Divest {
+                strategy: market.to_string(),
+                shares: None,
+            },
+            &[],
+        )
+        .unwrap();
+        let balance: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract,
+                &QueryMsg::GetBalance {
+                    address: OWNER.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(balance.balance, coins(230, "ustake"));
+        let held = app.wrap().query_balance(&contract, "ustake").unwrap();
+        assert_eq!(held, coin(230, "ustake"));
+        let res: InvestmentsResponse = app.wrap().query_wasm_smart(&contract, &query).unwrap();
+        assert_eq!(res.investments, vec![]);
+
+        // no new shares once the old ones are worth nothing
+        app.execute_contract(owner.clone(), contract.clone(), &invest, &[])
+            .unwrap();
+        app.wasm_sudo(
+            market,
+            &MoneyMarketSudoMsg::Lose {
+                amount: Uint128::new(150),
+            },
+        )
+        .unwrap();
+        let err = app
+            .execute_contract(
+                owner,
+                contract,
+                &ExecuteMsg::Invest {
+                    amount: coin(50, "ustake"),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::StrategyWorthless {}.to_string()
+        );
+    }
+
+    #[test]
+    fn try_swap() {
+        let owner = Addr::unchecked(OWNER);
+        let (mut app, contract, _) = mock_staking_app(1000);
+        let code_id = app.store_code(mock_pair());
+        let pair = app
+            .instantiate_contract(
+                code_id,
+                owner.clone(),
+                &PairInstantiateMsg {
+                    denoms: ["ustake".to_string(), "uosmo".to_string()],
+                    commission: Decimal::zero(),
+                },
+                &[],
+                "pair",
+                None,
+            )
+            .unwrap();
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: pair.to_string(),
+            amount: vec![coin(10000, "ustake"), coin(20000, "uosmo")],
+        }))
+        .unwrap();
+        let get_balance = |app: &App| -> Vec<Coin> {
+            let res: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    &contract,
+                    &QueryMsg::GetBalance {
+                        address: OWNER.to_string(),
+                    },
+                )
+                .unwrap();
+            res.balance
+        };
+
+        // only the owner sets routes
+        let msg = ExecuteMsg::UpdateRoute {
+            offer_denom: "ustake".to_string(),
+            ask_denom: "uosmo".to_string(),
+            pair: Some(pair.to_string()),
+        };
+        app.execute_contract(Addr::unchecked("anyone"), contract.clone(), &msg, &[])
+            .unwrap_err();
+        app.execute_contract(owner.clone(), contract.clone(), &msg, &[])
+            .unwrap();
+        let res: RouteResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &contract,
+                &QueryMsg::Route {
+                    offer_denom: "ustake".to_string(),
+                    ask_denom: "uosmo".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.pair, Some(pair.clone()));
+
+        // the saved half of a transfer is swapped as it comes in
+        let dca = ExecuteMsg::UpdateDca {
+            dca: Some(Dca {
+                ask_denom: "uosmo".to_string(),
+                belief_price: Decimal::percent(50),
+                max_slippage: Decimal::percent(5),
+            }),
+        };
+        app.execute_contract(owner.clone(), contract.clone(), &dca, &[])
+            .unwrap();
+        let transfer = ExecuteMsg::Transfer {
+            received_funds: None,
+            savings_rate: None,
+        };
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &transfer,
+            &coins(400, "ustake"),
+        )
+        .unwrap();
+        assert_eq!(get_balance(&app), coins(393, "uosmo"));
+
+        // a swap paying less than the saver's price leaves the savings as they are,
+        // the pool moved by the swap above no longer pays 1:0.5
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &ExecuteMsg::UpdateDca { dca: None },
+            &[],
+        )
+        .unwrap();
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &transfer,
+            &coins(400, "ustake"),
+        )
+        .unwrap();
+        let res = app
+            .execute_contract(
+                owner.clone(),
+                contract.clone(),
+                &ExecuteMsg::Swap {
+                    amount: coin(200, "ustake"),
+                    ask_denom: "uosmo".to_string(),
+                    belief_price: Decimal::percent(50),
+                    max_slippage: Decimal::percent(1),
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(res
+            .events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .any(|attr| attr.key == "swap_failed"));
+        assert_eq!(
+            get_balance(&app),
+            vec![coin(393, "uosmo"), coin(200, "ustake")]
+        );
+
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &ExecuteMsg::Swap {
+                amount: coin(200, "ustake"),
+                ask_denom: "uosmo".to_string(),
+                belief_price: Decimal::percent(52),
+                max_slippage: Decimal::percent(5),
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(get_balance(&app), coins(771, "uosmo"));
+        let held = app.wrap().query_all_balances(&contract).unwrap();
+        assert_eq!(held, coins(771, "uosmo"));
+
+        // a pool keeping more than max_slippage as its fee is reverted, the offer stays saved
+        let fee_pair = app
+            .instantiate_contract(
+                code_id,
+                owner.clone(),
+                &PairInstantiateMsg {
+                    denoms: ["ustake".to_string(), "uosmo".to_string()],
+                    commission: Decimal::percent(10),
+                },
+                &[],
+                "fee pair",
+                None,
+            )
+            .unwrap();
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: fee_pair.to_string(),
+            amount: vec![coin(10000, "ustake"), coin(20000, "uosmo")],
+        }))
+        .unwrap();
+        let msg = ExecuteMsg::UpdateRoute {
+            offer_denom: "uosmo".to_string(),
+            ask_denom: "ustake".to_string(),
+            pair: Some(fee_pair.to_string()),
+        };
+        app.execute_contract(owner.clone(), contract.clone(), &msg, &[])
+            .unwrap();
+        let res = app
+            .execute_contract(
+                owner.clone(),
+                contract.clone(),
+                &ExecuteMsg::Swap {
+                    amount: coin(100, "uosmo"),
+                    ask_denom: "ustake".to_string(),
+                    belief_price: Decimal::percent(200),
+                    max_slippage: Decimal::percent(5),
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(res
+            .events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .any(|attr| attr.key == "swap_failed"));
+        assert_eq!(get_balance(&app), coins(771, "uosmo"));
+        let held = app.wrap().query_all_balances(&contract).unwrap();
+        assert_eq!(held, coins(771, "uosmo"));
+        let reserves = app.wrap().query_all_balances(&fee_pair).unwrap();
+        assert_eq!(reserves, vec![coin(20000, "uosmo"), coin(10000, "ustake")]);
+
+        // dca swaps at the saver's price, a pool moved in the same block doesn't lower it
+        let dca = |belief_price: Decimal| ExecuteMsg::UpdateDca {
+            dca: Some(Dca {
+                ask_denom: "uosmo".to_string(),
+                belief_price,
+                max_slippage: Decimal::percent(5),
+            }),
+        };
+        let err = app
+            .execute_contract(owner.clone(), contract.clone(), &dca(Decimal::zero()), &[])
+            .unwrap_err();
+        assert_eq!(
+            err.root_cause().to_string(),
+            ContractError::InvalidPrice {}.to_string()
+        );
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &dca(Decimal::percent(54)),
+            &[],
+        )
+        .unwrap();
+        let attacker = Addr::unchecked("attacker");
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: attacker.to_string(),
+            amount: coins(5000, "ustake"),
+        }))
+        .unwrap();
+        let front_run = Pair(pair)
+            .swap(coin(5000, "ustake"), Decimal::one(), Decimal::one())
+            .unwrap();
+        app.execute(attacker, front_run).unwrap();
+        app.execute_contract(
+            owner.clone(),
+            contract.clone(),
+            &transfer,
+            &coins(400, "ustake"),
+        )
+        .unwrap();
+        assert_eq!(
+            get_balance(&app),
+            vec![coin(771, "uosmo"), coin(200, "ustake")]
+        );
     }
 }
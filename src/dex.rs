@@ -0,0 +1,94 @@
+use cosmwasm_std::{
+    to_binary, Addr, Coin, CosmosMsg, Decimal, QuerierWrapper, StdResult, Uint128, WasmMsg,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Astroport style pair interface, only native denoms are swapped
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AssetInfo {
+    Token { contract_addr: String },
+    NativeToken { denom: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Asset {
+    pub info: AssetInfo,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairExecuteMsg {
+    // Swap the attached offer_asset, failing when the price is further than
+    // max_spread from belief_price
+    Swap {
+        offer_asset: Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairQueryMsg {
+    // Return the reserves of both assets
+    Pool {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolResponse {
+    pub assets: Vec<Asset>,
+    pub total_share: Uint128,
+}
+
+impl PoolResponse {
+    pub fn amount_of(&self, denom: &str) -> Uint128 {
+        self.assets
+            .iter()
+            .find(|asset| {
+                matches!(&asset.info, AssetInfo::NativeToken { denom: native } if native == denom)
+            })
+            .map_or_else(Uint128::zero, |asset| asset.amount)
+    }
+}
+
+// Pool contract a route swaps through
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Pair(pub Addr);
+
+impl Pair {
+    pub fn addr(&self) -> &Addr {
+        &self.0
+    }
+
+    pub fn swap(
+        &self,
+        offer: Coin,
+        belief_price: Decimal,
+        max_spread: Decimal,
+    ) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_binary(&PairExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: offer.denom.clone(),
+                    },
+                    amount: offer.amount,
+                },
+                belief_price: Some(belief_price),
+                max_spread: Some(max_spread),
+                to: None,
+            })?,
+            funds: vec![offer],
+        }
+        .into())
+    }
+
+    pub fn query_pool(&self, querier: &QuerierWrapper) -> StdResult<PoolResponse> {
+        querier.query_wasm_smart(&self.0, &PairQueryMsg::Pool {})
+    }
+}
@@ -1,14 +1,31 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Coin, OverflowError, StdError, Timestamp, Uint128};
 use thiserror::Error;
 
+use crate::state::LockPolicy;
+
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Cannot migrate from {stored}")]
+    InvalidContract { stored: String },
+
+    #[error("Cannot migrate from version {stored} down to {current}")]
+    CannotDowngrade { stored: String, current: String },
+
+    #[error("Account Not Found")]
+    AccountNotFound {},
+
+    #[error("Account Already Exists")]
+    AccountExists {},
+
     #[error("Invalid Savings Rate")]
     InvalidSavingsRate {},
 
@@ -17,6 +34,159 @@ pub enum ContractError {
 
     #[error("Empty Transfer")]
     EmptyTransfer {},
+
+    #[error("Token Not Accepted: {token}")]
+    TokenNotAccepted { token: String },
+
+    #[error("Invalid Denom: {denom}")]
+    InvalidDenom { denom: String },
+
+    #[error("Funds Mismatch: expected {expected:?}, received {received:?}")]
+    FundsMismatch {
+        expected: Vec<Coin>,
+        received: Vec<Coin>,
+    },
+
+    #[error("No Pending Owner")]
+    NoPendingOwner {},
+
+    #[error("Ownership Proposal Expired")]
+    OwnershipProposalExpired {},
+
+    #[error("Period Too Long: at most {max} seconds")]
+    PeriodTooLong { max: u64 },
+
+    #[error("Invalid Split: {reason}")]
+    InvalidSplit { reason: String },
+
+    #[error("Invalid Pot Name: {name}")]
+    InvalidPotName { name: String },
+
+    #[error("Pot Already Exists: {name}")]
+    PotExists { name: String },
+
+    #[error("Pot Not Found: {name}")]
+    PotNotFound { name: String },
+
+    #[error("The default pot can't be renamed or closed")]
+    DefaultPot {},
+
+    #[error("Insufficient Funds")]
+    InsufficientFunds {},
+
+    #[error("Invalid Allocation")]
+    InvalidAllocation {},
+
+    #[error("Invalid Goal")]
+    InvalidGoal {},
+
+    #[error("Still Locked until {unlocks_at}")]
+    StillLocked { unlocks_at: LockPolicy },
+
+    #[error("Empty Withdrawal")]
+    EmptyWithdrawal {},
+
+    #[error("Withdrawals wait {delay} seconds, request one instead")]
+    WithdrawalDelayed { delay: u64 },
+
+    #[error("Withdrawal Not Found: {id}")]
+    WithdrawalNotFound { id: u64 },
+
+    #[error("Withdrawal available at {available_at}")]
+    WithdrawalNotReady { available_at: Timestamp },
+
+    #[error("Invalid Penalty")]
+    InvalidPenalty {},
+
+    #[error("Early withdrawal needs a penalty or a guardian")]
+    EarlyWithdrawDisabled {},
+
+    #[error("Paused")]
+    Paused {},
+
+    #[error("Invalid Approvers: {reason}")]
+    InvalidApprovers { reason: String },
+
+    #[error("Proposal Not Found: {id}")]
+    ProposalNotFound { id: u64 },
+
+    #[error("Proposal Expired: {id}")]
+    ProposalExpired { id: u64 },
+
+    #[error("Already Approved")]
+    AlreadyApproved {},
+
+    #[error("Not Approved")]
+    NotApproved {},
+
+    #[error("No Pending Approvers")]
+    NoPendingApprovers {},
+
+    #[error("Approvers change effective at {effective_at}")]
+    ApproversChangeNotReady { effective_at: Timestamp },
+
+    #[error("Invalid Guardian")]
+    InvalidGuardian {},
+
+    #[error("No Pending Guardian")]
+    NoPendingGuardian {},
+
+    #[error("Guardian change effective at {effective_at}")]
+    GuardianChangeNotReady { effective_at: Timestamp },
+
+    #[error("Already waiting for the guardian")]
+    GuardianRequestPending {},
+
+    #[error("No request for the guardian")]
+    NoGuardianRequest {},
+
+    #[error("Invalid Inheritance: {reason}")]
+    InvalidInheritance { reason: String },
+
+    #[error("Inheritance claimable at {claimable_at}")]
+    InheritanceNotClaimable { claimable_at: Timestamp },
+
+    #[error("Invalid Validator: {validator}")]
+    InvalidValidator { validator: String },
+
+    #[error("Nothing Staked")]
+    NotStaked {},
+
+    #[error("Unstake due at {due_at}")]
+    UnstakeNotDue { due_at: Timestamp },
+
+    #[error("Savings are staked, unstake them and wait for the unbonding")]
+    SavingsStaked {},
+
+    #[error("Unknown Reply: {id}")]
+    UnknownReply { id: u64 },
+
+    #[error("Invalid Strategy: {strategy}")]
+    InvalidStrategy { strategy: String },
+
+    #[error("No Strategy")]
+    NoStrategy {},
+
+    #[error("Strategy takes {expected}, not {received}")]
+    StrategyDenomMismatch { expected: String, received: String },
+
+    #[error("Strategy shares are worth nothing")]
+    StrategyWorthless {},
+
+    #[error("No Route from {offer_denom} to {ask_denom}")]
+    NoRoute {
+        offer_denom: String,
+        ask_denom: String,
+    },
+
+    #[error("Invalid Slippage")]
+    InvalidSlippage {},
+
+    #[error("Invalid Price")]
+    InvalidPrice {},
+
+    #[error("Slippage Exceeded: expected at least {min_out}, received {received}")]
+    SlippageExceeded { min_out: Uint128, received: Uint128 },
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
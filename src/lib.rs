@@ -1,6 +1,10 @@
 pub mod contract;
+pub mod dex;
 mod error;
 pub mod msg;
 pub mod state;
+pub mod strategy;
+#[cfg(test)]
+mod testing;
 
 pub use crate::error::ContractError;
@@ -1,35 +1,495 @@
-use cosmwasm_std::Coin;
+use cosmwasm_std::{Addr, Coin, Decimal, Timestamp, Uint128};
+use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::State;
+use crate::state::{
+    AllocationMode, ApproverChange, ApproverSet, Dca, Delegation, DenomStats, DepositRecord,
+    GuardianChange, GuardianRequest, LockPolicy, PauseScope, Pot, Proposal, RoundingMode,
+    Unbonding, WithdrawalRequest,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
-    pub savings_rate: u8,
+    // Address that owns the contract, an account is opened for it with the settings below
+    pub owner: String,
+    // In basis points, 1250 saves 12.5% of every transfer
+    pub savings_rate: u16,
+    // Defaults to rounding the payout down
+    pub rounding: Option<RoundingMode>,
+    // Savings can't be flushed before this time or height
+    pub lock: Option<LockPolicy>,
+    // Savings are sent back to the owner once this much is saved and the lock has opened
+    pub goal: Option<Coin>,
+    // Share the spending portion between several recipients
+    pub split: Option<SpendingSplitMsg>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendingSplitMsg {
+    pub recipients: Vec<RecipientWeight>,
+    pub dust_recipient: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApproverSetMsg {
+    pub approvers: Vec<String>,
+    pub required: u32,
+    pub threshold: Vec<Coin>,
+    pub expires_in: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PenaltyMsg {
+    // In basis points of the withdrawn amount
+    pub rate: u16,
+    // Burned when unset
+    pub recipient: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InheritanceMsg {
+    pub beneficiary: String,
+    // In seconds without any execute from the saver
+    pub inactivity_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecipientWeight {
+    pub address: String,
+    // In basis points
+    pub weight: u16,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    // Transfer the received funds, not the total funds in the contract
+    // Open an account for the sender with its own rate, lock and goal
+    OpenAccount {
+        savings_rate: u16,
+        rounding: Option<RoundingMode>,
+        lock: Option<LockPolicy>,
+        goal: Option<Coin>,
+        split: Option<SpendingSplitMsg>,
+        // Can only be added while the lock is still open
+        penalty: Option<PenaltyMsg>,
+    },
+    // Transfer the attached funds, not the total funds in the contract.
+    // received_funds optionally declares what is expected to be attached
     Transfer {
-        received_funds: Coin,
-        savings_rate: u8,
+        received_funds: Option<Vec<Coin>>,
+        // Overrides the stored rate for this transfer only
+        savings_rate: Option<u16>,
+    },
+    // CW20 hook, the tokens are split like a Transfer of the sender's funds
+    Receive(Cw20ReceiveMsg),
+    // Called by the owner to accept or stop accepting a CW20 token
+    UpdateToken {
+        token: String,
+        accepted: bool,
     },
-    //Take all the contract's funds
+    //Take all the sender's savings, only when no withdrawal delay is set
     Flush {},
+    // Set funds aside to be withdrawn once the withdrawal delay has passed
+    RequestWithdrawal {
+        amount: Uint128,
+        denom: String,
+    },
+    // Pay out a request whose delay has passed
+    ExecuteWithdrawal {
+        id: u64,
+    },
+    // Drop a pending request, its funds stay saved
+    CancelWithdrawal {
+        id: u64,
+    },
+    // Change the sender's savings rate, rounding and withdrawal delay,
+    // a shorter delay only applies once the current one has passed
+    UpdateConfig {
+        savings_rate: Option<u16>,
+        rounding: Option<RoundingMode>,
+        withdrawal_delay: Option<u64>,
+    },
+    // Change where the sender's spending portion goes, unset pays the sender
+    UpdateSplit {
+        split: Option<SpendingSplitMsg>,
+    },
+    // Take all the sender's savings now, even while locked, minus the penalty.
+    // While locked, a guardian has to approve it first
+    EarlyWithdraw {},
+    // Require approvals to withdraw more than the threshold within a day, unset disables it.
+    // Replacing or removing the approvers only takes effect after a delay
+    UpdateApprovers {
+        approvers: Option<ApproverSetMsg>,
+    },
+    // Apply the proposed approvers once the delay has passed
+    ConfirmApprovers {},
+    // Called by an approver, the last approval needed pays out the proposal
+    ApproveProposal {
+        id: u64,
+    },
+    // Called by an approver to take back their approval
+    RevokeApproval {
+        id: u64,
+    },
+    // Called by the guardian to pay out the owner's early withdrawal
+    Approve {
+        owner: String,
+    },
+    // Called by the guardian to drop the owner's early withdrawal
+    Reject {
+        owner: String,
+    },
+    // Set the sender's guardian, replacing or removing one only takes effect after a delay
+    ProposeGuardian {
+        guardian: Option<String>,
+    },
+    // Apply the proposed guardian once the delay has passed
+    ConfirmGuardian {},
+    // Change the early withdrawal penalty, while locked it can only be raised for the same recipient
+    UpdatePenalty {
+        penalty: Option<PenaltyMsg>,
+    },
+    // Change who can claim the sender's savings after inactivity, unset disables it
+    UpdateInheritance {
+        inheritance: Option<InheritanceMsg>,
+    },
+    // Called by the beneficiary to take the savings of an inactive saver
+    ClaimInheritance {
+        owner: String,
+    },
+    // Add an empty pot to the sender's account
+    CreatePot {
+        name: String,
+    },
+    RenamePot {
+        name: String,
+        new_name: String,
+    },
+    // Remove a pot, its savings move to the default pot
+    ClosePot {
+        name: String,
+    },
+    MovePotFunds {
+        from: String,
+        to: String,
+        amount: Coin,
+    },
+    // Replace the allocation table, unlisted pots get nothing
+    SetAllocations {
+        allocations: Vec<PotAllocation>,
+    },
+    // Amount a pot is filled up to by the waterfall allocation
+    SetPotTarget {
+        name: String,
+        target: Option<Coin>,
+    },
+    // Switch between fixed percentages and the waterfall
+    SetAllocationMode {
+        mode: AllocationMode,
+    },
+    // Called by the owner or pause guardian, switches off deposits, withdrawals or both
+    Pause {
+        scope: Option<PauseScope>,
+    },
+    Unpause {
+        scope: Option<PauseScope>,
+    },
+    // Called by the owner to choose who else can pause, unset removes them
+    SetPauseGuardian {
+        guardian: Option<String>,
+    },
+    // Called by the owner to choose the validator locked savings are delegated to,
+    // unset keeps new savings liquid. The unbonding period can't be shortened while
+    // anything is delegated
+    UpdateStaking {
+        validator: Option<String>,
+        unbonding_period: u64,
+    },
+    // Start unbonding the account's delegation, anyone can once the unlock is
+    // within the unbonding period, the saver at any time
+    Unstake {
+        address: String,
+    },
+    // Claim the staking rewards and delegate them again, callable by anyone
+    Compound {},
+    // Called by the owner to choose the strategy savings can be invested in,
+    // unset stops new investments
+    UpdateStrategy {
+        strategy: Option<String>,
+    },
+    // Move some of the sender's savings into the current strategy, in the denom it holds
+    Invest {
+        amount: Coin,
+    },
+    // Take shares out of a strategy back into the sender's default pot, all of them when unset
+    Divest {
+        strategy: String,
+        shares: Option<Uint128>,
+    },
+    // Called by the owner to set the pair swapping offer_denom into ask_denom, unset removes the route
+    UpdateRoute {
+        offer_denom: String,
+        ask_denom: String,
+        pair: Option<String>,
+    },
+    // Swap the saved portion of every transfer into another asset, unset stops it
+    UpdateDca {
+        dca: Option<Dca>,
+    },
+    // Swap some of the sender's savings, the output is saved like a transfer.
+    // Left saved when the pool pays less than belief_price, the offer paid per
+    // unit of ask_denom, minus max_slippage
+    Swap {
+        amount: Coin,
+        ask_denom: String,
+        belief_price: Decimal,
+        max_slippage: Decimal,
+    },
+    // Internal, swap the next order through its pair and check what it paid out
+    ExecuteSwapOrder {},
+    // Internal, fail the swap when the order received less than its minimum
+    CheckSwapOrder {},
+    // Propose a new owner, who has to accept before taking over and must not hold an account
+    ProposeOwner {
+        new_owner: String,
+        expires_in: Option<u64>,
+    },
+    // Called by the proposed owner to complete the transfer, the owner's account moves to them
+    AcceptOwnership {},
+    // Drop the pending proposal
+    CancelOwnershipTransfer {},
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PotAllocation {
+    pub name: String,
+    // In basis points
+    pub allocation: u16,
+}
+
+// Message attached to a CW20 Send into this contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Transfer { savings_rate: Option<u16> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    // Return the contract's balance
-    GetBalance {},
+    // Return the savings held for an account
+    GetBalance {
+        address: String,
+    },
+    // Return the current and pending owner
+    GetOwnership {},
+    // Return what is paused, by whom and since when
+    GetPause {},
+    // Return the account's lock and how long until it opens
+    GetLock {
+        address: String,
+    },
+    // Return how far the account is from its goal
+    GetProgress {
+        address: String,
+    },
+    // Return the account's deposit ledger, oldest first
+    Deposits {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Return the account's running totals for every denom
+    Stats {
+        address: String,
+    },
+    // Return the account's proposals that can still be approved
+    Proposals {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // Return the account's guardian and anything waiting on it
+    GetGuardian {
+        address: String,
+    },
+    // Return the account's approvers and any change waiting to apply
+    GetApprovers {
+        address: String,
+    },
+    // Return the account's beneficiary and when they can claim
+    GetInheritance {
+        address: String,
+    },
+    // Return the account's pending withdrawal requests
+    Withdrawals {
+        address: String,
+    },
+    // Return the account's delegation, unbonding savings and when they are unstaked
+    GetStake {
+        address: String,
+    },
+    // Return the account's shares in every strategy and what they are worth
+    Investments {
+        address: String,
+    },
+    // Return whether the CW20 token is accepted
+    Token {
+        token: String,
+    },
+    // Return the pair swapping offer_denom into ask_denom
+    Route {
+        offer_denom: String,
+        ask_denom: String,
+    },
+    // Return the account's pots and their balances
+    Pots {
+        address: String,
+    },
+    // Return where a transfer of funds would land without executing it
+    SimulateTransfer {
+        address: String,
+        funds: Vec<Coin>,
+        savings_rate: Option<u16>,
+    },
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct BalanceResponse {
+    // Everything saved, delegated and unbonding included
     pub(crate) balance: Vec<Coin>,
+    // Part that can be withdrawn right away
+    pub(crate) liquid: Vec<Coin>,
+    pub(crate) delegated: Vec<Coin>,
+    pub(crate) unbonding: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OwnershipResponse {
+    pub owner: Addr,
+    pub pending_owner: Option<Addr>,
+    pub pending_expires_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockResponse {
+    pub unlocks_at: Option<LockPolicy>,
+    pub locked: bool,
+    pub remaining_seconds: Option<u64>,
+    pub remaining_blocks: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProgressResponse {
+    pub saved: Coin,
+    pub target: Coin,
+    pub percent_complete: Decimal,
+    pub reached: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositsResponse {
+    pub deposits: Vec<DepositRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    pub stats: Vec<DenomStats>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PotsResponse {
+    pub pots: Vec<Pot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateTransferResponse {
+    // Spending portion, before it is split between recipients
+    pub payout: Vec<Coin>,
+    // Savings sent back to the owner once every waterfall target is met
+    pub returned: Vec<Coin>,
+    pub pots: Vec<PotShare>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PotShare {
+    pub name: String,
+    pub amount: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WithdrawalsResponse {
+    pub withdrawals: Vec<WithdrawalRequest>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InheritanceResponse {
+    pub beneficiary: Option<Addr>,
+    pub inactivity_period: Option<u64>,
+    pub last_activity: Timestamp,
+    pub claimable_at: Option<Timestamp>,
+    // 0 once the beneficiary can claim
+    pub remaining_seconds: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GuardianResponse {
+    pub guardian: Option<Addr>,
+    pub pending_change: Option<GuardianChange>,
+    pub pending_request: Option<GuardianRequest>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApproversResponse {
+    pub approvers: Option<ApproverSet>,
+    pub pending_change: Option<ApproverChange>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalsResponse {
+    pub proposals: Vec<Proposal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenResponse {
+    pub accepted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RouteResponse {
+    pub pair: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InvestmentsResponse {
+    pub investments: Vec<Investment>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Investment {
+    pub strategy: Addr,
+    pub shares: Uint128,
+    pub value: Coin,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeResponse {
+    pub delegation: Option<Delegation>,
+    pub unbonding: Vec<Unbonding>,
+    // Anyone can start unbonding from this time, unset while nothing is delegated
+    pub unstake_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PauseResponse {
+    pub pause_guardian: Option<Addr>,
+    pub deposits: bool,
+    pub withdrawals: bool,
+    pub paused_by: Option<Addr>,
+    pub paused_at: Option<Timestamp>,
 }
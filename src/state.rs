@@ -1,24 +1,526 @@
+use std::fmt;
+
 use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Coin, Storage};
-use cw_storage_plus::Item;
+use cosmwasm_std::{
+    Addr, BlockInfo, Coin, Decimal, Empty, StdError, StdResult, Storage, Timestamp, Uint128,
+};
+use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub owner: Addr,
     pub amount_received: Vec<Coin>,
-    pub savings_rate: u8,
+    // Can pause and unpause next to the owner
+    pub pause_guardian: Option<Addr>,
+    // Locked savings are delegated to this validator, new savings stay liquid when unset
+    pub validator: Option<String>,
+    // Seconds the chain takes to unbond, undelegating starts this long before an unlock
+    pub unbonding_period: u64,
+    // Yield strategy savings can be invested in, see crate::strategy
+    pub strategy: Option<Addr>,
+}
+
+// Which fund movements are switched off
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct PauseInfo {
+    pub deposits: bool,
+    pub withdrawals: bool,
+    pub paused_by: Option<Addr>,
+    pub paused_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PauseScope {
+    All,
+    Deposits,
+    Withdrawals,
+}
+
+// A single saver's settings and the savings tracked for them
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Account {
+    // In basis points, 10000 saves everything
+    pub savings_rate: u16,
+    pub rounding: RoundingMode,
+    pub lock: Option<LockPolicy>,
+    pub goal: Option<SavingsGoal>,
+    // Where the spending portion goes, the saver gets it all when unset
+    pub split: Option<SpendingSplit>,
+    // How saved funds are spread over the pots
+    pub allocation: AllocationMode,
+    // Seconds between requesting a withdrawal and executing it, 0 allows Flush
+    pub withdrawal_delay: u64,
+    pub pending_withdrawal_delay: Option<DelayChange>,
+    pub inheritance: Option<Inheritance>,
+    // Withheld when breaking the lock, early withdrawal is disabled when unset
+    pub penalty: Option<Penalty>,
+    // Has to approve early withdrawals while the savings are locked
+    pub guardian: Option<Addr>,
+    pub pending_guardian: Option<GuardianChange>,
+    // Has to approve large withdrawals
+    pub approvers: Option<ApproverSet>,
+    pub pending_approvers: Option<ApproverChange>,
+    // Withdrawals counted against the approvers' threshold
+    pub recent_withdrawals: Vec<RecentWithdrawal>,
+    // Time of the saver's last execute
+    pub last_activity: Timestamp,
+    // Part of the balance delegated to a validator
+    pub delegation: Option<Delegation>,
+    // Part of the balance on its way back from the validator
+    pub unbonding: Vec<Unbonding>,
+    // Swaps the saved portion of every transfer
+    pub dca: Option<Dca>,
+    pub balance: Vec<Coin>,
+}
+
+impl Account {
+    pub fn balance_of(&self, denom: &str) -> Uint128 {
+        self.balance
+            .iter()
+            .find(|saved| saved.denom == denom)
+            .map_or_else(Uint128::zero, |saved| saved.amount)
+    }
+
+    // Part of the balance that isn't delegated or still unbonding at now
+    pub fn liquid_of(&self, denom: &str, now: Timestamp) -> Uint128 {
+        let staked = self
+            .delegation
+            .iter()
+            .map(|delegation| &delegation.amount)
+            .chain(
+                self.unbonding
+                    .iter()
+                    .filter(|unbonding| unbonding.completes_at > now)
+                    .map(|unbonding| &unbonding.amount),
+            )
+            .filter(|staked| staked.denom == denom)
+            .fold(Uint128::zero(), |total, staked| {
+                total.saturating_add(staked.amount)
+            });
+        self.balance_of(denom).saturating_sub(staked)
+    }
+
+    // Withdrawal delay in force at now, a shorter one waits for its effective time
+    pub fn withdrawal_delay_at(&self, now: Timestamp) -> u64 {
+        match &self.pending_withdrawal_delay {
+            Some(change) if now >= change.effective_at => change.delay,
+            _ => self.withdrawal_delay,
+        }
+    }
+
+    pub fn credit(&mut self, amount: &Coin) -> StdResult<()> {
+        add_coin(&mut self.balance, amount)
+    }
+
+    pub fn debit(&mut self, amount: &Coin) -> StdResult<()> {
+        sub_coin(&mut self.balance, amount)
+    }
+}
+
+// Shorter withdrawal delay, only applies once the current delay has passed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DelayChange {
+    pub delay: u64,
+    pub effective_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Delegation {
+    pub validator: String,
+    pub amount: Coin,
+}
+
+// Undelegated savings, paid back to the contract at completes_at
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Unbonding {
+    pub amount: Coin,
+    pub completes_at: Timestamp,
+}
+
+// Asset saved funds are swapped into, only denoms with a route are swapped
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Dca {
+    pub ask_denom: String,
+    // Offer paid per unit of ask_denom the saver expects, the pool's price in
+    // the same block can be moved by whoever trades around the transfer
+    pub belief_price: Decimal,
+    // Largest shortfall from belief_price, 0.01 is 1%
+    pub max_slippage: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Penalty {
+    // In basis points of the withdrawn amount
+    pub rate: u16,
+    // Burned when unset
+    pub recipient: Option<Addr>,
+}
+
+// M-of-N approvers for withdrawals over the threshold
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApproverSet {
+    pub approvers: Vec<Addr>,
+    // Approvals needed to pay out
+    pub required: u32,
+    // Largest amount of each denom withdrawn within a day without approvals,
+    // unlisted denoms always need them
+    pub threshold: Vec<Coin>,
+    // Seconds a proposal stays open
+    pub expires_in: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecentWithdrawal {
+    pub amount: Coin,
+    pub at: Timestamp,
+}
+
+// Replacement approver set, None removes the approvals
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ApproverChange {
+    pub approvers: Option<ApproverSet>,
+    pub effective_at: Timestamp,
+}
+
+// Withdrawal collecting approvals
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub id: u64,
+    pub owner: Addr,
+    pub kind: ProposalKind,
+    pub amount: Vec<Coin>,
+    pub approvals: Vec<Addr>,
+    pub expires_at: Timestamp,
+}
+
+// How an approved proposal pays out
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalKind {
+    Flush {},
+    // The owner's withdrawal request
+    Withdrawal { id: u64 },
+    // Less the penalty, if one is set
+    EarlyWithdraw {},
+    // Paid to the beneficiary
+    Inheritance { beneficiary: Addr },
+}
+
+// Replacement guardian, None removes the guardian
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GuardianChange {
+    pub guardian: Option<Addr>,
+    pub effective_at: Timestamp,
+}
+
+// Early withdrawal waiting for the guardian
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GuardianRequest {
+    pub requested_at: Timestamp,
+}
+
+// Who can claim the savings once the saver has been inactive for long enough
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Inheritance {
+    pub beneficiary: Addr,
+    // In seconds
+    pub inactivity_period: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AllocationMode {
+    // Every pot takes its allocation of each saved amount
+    #[default]
+    Fixed,
+    // Pots are filled up to their target one after the other
+    Waterfall {
+        order: Vec<String>,
+        overflow: Overflow,
+    },
+}
+
+// Where savings go once every waterfall target is met
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Overflow {
+    DefaultPot,
+    Owner,
+}
+
+// Named share of an account's savings, all pots add up to the account balance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Pot {
+    pub name: String,
+    // In basis points of every saved amount, the default pot takes what is left
+    pub allocation: u16,
+    // Waterfall allocation stops filling the pot at this amount
+    pub target: Option<Coin>,
+    pub balance: Vec<Coin>,
+}
+
+impl Pot {
+    pub fn new(name: &str) -> Self {
+        Pot {
+            name: name.to_string(),
+            allocation: 0,
+            target: None,
+            balance: vec![],
+        }
+    }
+
+    pub fn balance_of(&self, denom: &str) -> Uint128 {
+        self.balance
+            .iter()
+            .find(|saved| saved.denom == denom)
+            .map_or_else(Uint128::zero, |saved| saved.amount)
+    }
+
+    pub fn credit(&mut self, amount: &Coin) -> StdResult<()> {
+        add_coin(&mut self.balance, amount)
+    }
+
+    pub fn debit(&mut self, amount: &Coin) -> StdResult<()> {
+        sub_coin(&mut self.balance, amount)
+    }
+}
+
+fn add_coin(balance: &mut Vec<Coin>, amount: &Coin) -> StdResult<()> {
+    match balance.iter().position(|saved| saved.denom == amount.denom) {
+        Some(i) => balance[i].amount = balance[i].amount.checked_add(amount.amount)?,
+        None => balance.push(amount.clone()),
+    }
+    balance.retain(|saved| !saved.amount.is_zero());
+    Ok(())
+}
+
+fn sub_coin(balance: &mut Vec<Coin>, amount: &Coin) -> StdResult<()> {
+    if amount.amount.is_zero() {
+        return Ok(());
+    }
+    let saved = balance
+        .iter_mut()
+        .find(|saved| saved.denom == amount.denom)
+        .ok_or_else(|| StdError::generic_err(format!("No {} saved", amount.denom)))?;
+    saved.amount = saved.amount.checked_sub(amount.amount)?;
+    balance.retain(|saved| !saved.amount.is_zero());
+    Ok(())
+}
+
+// Spending portion shared between recipients by weight
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendingSplit {
+    pub recipients: Vec<Recipient>,
+    // Receives whatever is left over after rounding each share down
+    pub dust_recipient: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Recipient {
+    pub address: Addr,
+    // In basis points, the weights of a split add up to 10000
+    pub weight: u16,
+}
+
+// How the payout is rounded, whatever is left over stays in savings
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    #[default]
+    Floor,
+    Ceil,
+    Bankers,
+}
+
+// Amount of a single denom to save before the funds are released
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SavingsGoal {
+    pub target: Coin,
+    pub reached: bool,
+}
+
+// Point before which the savings can't be withdrawn
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LockPolicy {
+    AtTime(Timestamp),
+    AtHeight(u64),
+}
+
+impl LockPolicy {
+    pub fn is_locked(&self, block: &BlockInfo) -> bool {
+        match self {
+            LockPolicy::AtTime(time) => block.time < *time,
+            LockPolicy::AtHeight(height) => block.height < *height,
+        }
+    }
+}
+
+impl fmt::Display for LockPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LockPolicy::AtTime(time) => write!(f, "time: {}", time.seconds()),
+            LockPolicy::AtHeight(height) => write!(f, "height: {}", height),
+        }
+    }
 }
 
 pub const STATE: Item<State> = Item::new("state");
+// Only present while something is paused
+pub const PAUSE: Item<PauseInfo> = Item::new("pause");
+pub const ACCOUNTS: Map<&Addr, Account> = Map::new("accounts");
+// Keyed by saver, then pot name
+pub const POTS: Map<(&Addr, &str), Pot> = Map::new("pots");
+// Proposal ids are unique across all savers
+pub const PROPOSAL_SEQ: Item<u64> = Item::new("proposal_seq");
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+// The open inheritance proposal of a saver, dropped when the saver shows up
+pub const INHERITANCE_PROPOSALS: Map<&Addr, u64> = Map::new("inheritance_proposals");
+// Keyed by saver, then strategy
+pub const INVESTMENTS: Map<(&Addr, &Addr), Uint128> = Map::new("investments");
+// All the shares of a strategy, its value is split between them
+pub const STRATEGY_SHARES: Map<&Addr, Uint128> = Map::new("strategy_shares");
+
+// CW20 tokens the owner accepts deposits of
+pub const TOKENS: Map<&Addr, Empty> = Map::new("tokens");
+
+// Pair contracts swaps go through, keyed by offer denom, then ask denom
+pub const ROUTES: Map<(&str, &str), Addr> = Map::new("routes");
+
+// Savings taken out for a swap, credited with the output in the reply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapOrder {
+    pub saver: Addr,
+    pub offer: Coin,
+    pub ask_denom: String,
+    pub min_out: Uint128,
+    pub pair: Addr,
+    // Spot price the pair checks its own spread against
+    pub belief_price: Decimal,
+    pub max_slippage: Decimal,
+}
+
+// Swaps being executed, one order per reply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Swapping {
+    // Held of every ask denom before the next output comes in
+    pub balances: Vec<Coin>,
+    pub orders: Vec<SwapOrder>,
+}
+
+// Only present while swaps are being executed
+pub const SWAPPING: Item<Swapping> = Item::new("swapping");
+
+// Rewards being claimed, one validator per reply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Compounding {
+    // Bonded denom held before any reward came in
+    pub balance: Uint128,
+    pub validators: Vec<String>,
+}
+
+// What the savers have delegated to each validator, as recorded in their accounts.
+// A slashed validator holds less than this
+pub const DELEGATED: Map<&str, Uint128> = Map::new("delegated");
+// Savers with a delegation, keyed by validator then saver
+pub const STAKERS: Map<(&str, &Addr), Empty> = Map::new("stakers");
+// Only present while a Compound is being executed
+pub const COMPOUNDING: Item<Compounding> = Item::new("compounding");
+// At most one per saver
+pub const GUARDIAN_REQUESTS: Map<&Addr, GuardianRequest> = Map::new("guardian_requests");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub address: Addr,
+    pub expires_at: Option<Timestamp>,
+}
+
+pub const PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+
+// One entry per denom received in a Transfer
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DepositRecord {
+    pub id: u64,
+    pub timestamp: Timestamp,
+    pub height: u64,
+    pub denom: String,
+    pub gross_amount: Uint128,
+    pub saved_amount: Uint128,
+    pub paid_out_amount: Uint128,
+    pub savings_rate: u16,
+}
+
+// Running totals for a single denom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DenomStats {
+    pub denom: String,
+    pub total_received: Uint128,
+    pub total_saved: Uint128,
+    pub total_paid_out: Uint128,
+    pub total_withdrawn: Uint128,
+    // Withheld from early withdrawals, included in total_withdrawn
+    pub total_penalty: Uint128,
+    // Staking rewards added to the savings, not part of total_saved
+    pub total_compounded: Uint128,
+    pub transfers: u64,
+}
+
+impl DenomStats {
+    pub fn new(denom: &str) -> Self {
+        DenomStats {
+            denom: denom.to_string(),
+            total_received: Uint128::zero(),
+            total_saved: Uint128::zero(),
+            total_paid_out: Uint128::zero(),
+            total_withdrawn: Uint128::zero(),
+            total_penalty: Uint128::zero(),
+            total_compounded: Uint128::zero(),
+            transfers: 0,
+        }
+    }
+}
+
+// Keyed by saver, then denom
+pub const STATS: Map<(&Addr, &str), DenomStats> = Map::new("stats");
+
+// Funds set aside until the withdrawal delay has passed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WithdrawalRequest {
+    pub id: u64,
+    pub amount: Coin,
+    pub requested_at: Timestamp,
+    pub available_at: Timestamp,
+}
+
+// Withdrawal ids are unique across all savers
+pub const WITHDRAWAL_SEQ: Item<u64> = Item::new("withdrawal_seq");
+pub const WITHDRAWALS: Map<(&Addr, u64), WithdrawalRequest> = Map::new("withdrawals");
+
+// Deposit ids are unique across all savers
+pub const DEPOSIT_SEQ: Item<u64> = Item::new("deposit_seq");
+pub const DEPOSITS: Map<(&Addr, u64), DepositRecord> = Map::new("deposits");
+
+// Layout of the single-owner state written under the legacy config key,
+// and by the first release under the state key
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyState {
+    pub owner: Addr,
+    pub amount_received: Vec<Coin>,
+    // In percent
+    pub savings_rate: u8,
+}
+
+pub const LEGACY_STATE: Item<LegacyState> = Item::new("state");
 
 const CONFIG_KEY: &[u8] = b"config";
-pub fn config(storage: &mut dyn Storage) -> Singleton<State> {
+pub fn config(storage: &mut dyn Storage) -> Singleton<'_, LegacyState> {
     singleton(storage, CONFIG_KEY)
 }
 
-pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<State> {
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, LegacyState> {
     singleton_read(storage, CONFIG_KEY)
 }
@@ -0,0 +1,63 @@
+use cosmwasm_std::{to_binary, Addr, Coin, CosmosMsg, QuerierWrapper, StdResult, Uint128, WasmMsg};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Execute messages every yield strategy contract has to accept
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyExecuteMsg {
+    // Put the attached funds to work for the sender
+    Deposit {},
+    // Send amount of the sender's position back to them
+    Withdraw { amount: Uint128 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StrategyQueryMsg {
+    // Return what the address could withdraw right now
+    Value { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ValueResponse {
+    pub value: Coin,
+}
+
+// Yield source savings can be invested in, a contract implementing the messages above
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Strategy(pub Addr);
+
+impl Strategy {
+    pub fn addr(&self) -> &Addr {
+        &self.0
+    }
+
+    pub fn deposit(&self, amount: Coin) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_binary(&StrategyExecuteMsg::Deposit {})?,
+            funds: vec![amount],
+        }
+        .into())
+    }
+
+    pub fn withdraw(&self, amount: Uint128) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.0.to_string(),
+            msg: to_binary(&StrategyExecuteMsg::Withdraw { amount })?,
+            funds: vec![],
+        }
+        .into())
+    }
+
+    pub fn query_value(&self, querier: &QuerierWrapper, address: &Addr) -> StdResult<Coin> {
+        let res: ValueResponse = querier.query_wasm_smart(
+            &self.0,
+            &StrategyQueryMsg::Value {
+                address: address.to_string(),
+            },
+        )?;
+        Ok(res.value)
+    }
+}
@@ -0,0 +1,226 @@
+// Contracts the savings contract talks to, for cw-multi-test
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
+use cw_multi_test::{Contract, ContractWrapper};
+use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::dex::{Asset, AssetInfo, PairExecuteMsg, PairQueryMsg, PoolResponse};
+use crate::strategy::{StrategyExecuteMsg, StrategyQueryMsg, ValueResponse};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MoneyMarketInstantiateMsg {
+    pub denom: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MoneyMarketSudoMsg {
+    // Bad debt, burns amount of what the market holds
+    Lose { amount: Uint128 },
+}
+
+// Lends out a single denom, interest is whatever is sent to it on top of the deposits
+const MARKET_DENOM: Item<String> = Item::new("denom");
+const MARKET_TOTAL_SHARES: Item<Uint128> = Item::new("total_shares");
+const MARKET_SHARES: Map<&Addr, Uint128> = Map::new("shares");
+
+fn market_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: MoneyMarketInstantiateMsg,
+) -> StdResult<Response> {
+    MARKET_DENOM.save(deps.storage, &msg.denom)?;
+    MARKET_TOTAL_SHARES.save(deps.storage, &Uint128::zero())?;
+    Ok(Response::new())
+}
+
+fn market_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: StrategyExecuteMsg,
+) -> StdResult<Response> {
+    let denom = MARKET_DENOM.load(deps.storage)?;
+    let total_shares = MARKET_TOTAL_SHARES.load(deps.storage)?;
+    let held = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    let shares = MARKET_SHARES
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    match msg {
+        StrategyExecuteMsg::Deposit {} => {
+            let amount = match info.funds.as_slice() {
+                [coin] if coin.denom == denom => coin.amount,
+                _ => return Err(StdError::generic_err("Only deposits of one denom")),
+            };
+            // the deposit is already part of held
+            let before = held.checked_sub(amount)?;
+            let minted = if total_shares.is_zero() || before.is_zero() {
+                amount
+            } else {
+                amount.multiply_ratio(total_shares, before)
+            };
+            MARKET_SHARES.save(deps.storage, &info.sender, &shares.checked_add(minted)?)?;
+            MARKET_TOTAL_SHARES.save(deps.storage, &total_shares.checked_add(minted)?)?;
+            Ok(Response::new())
+        }
+        StrategyExecuteMsg::Withdraw { amount } => {
+            let burned = amount.multiply_ratio(total_shares, held);
+            MARKET_SHARES.save(deps.storage, &info.sender, &shares.checked_sub(burned)?)?;
+            MARKET_TOTAL_SHARES.save(deps.storage, &total_shares.checked_sub(burned)?)?;
+            Ok(Response::new().add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![Coin { denom, amount }],
+            }))
+        }
+    }
+}
+
+fn market_sudo(deps: DepsMut, _env: Env, msg: MoneyMarketSudoMsg) -> StdResult<Response> {
+    let MoneyMarketSudoMsg::Lose { amount } = msg;
+    let denom = MARKET_DENOM.load(deps.storage)?;
+    Ok(Response::new().add_message(BankMsg::Burn {
+        amount: vec![Coin { denom, amount }],
+    }))
+}
+
+fn market_query(deps: Deps, env: Env, msg: StrategyQueryMsg) -> StdResult<Binary> {
+    let StrategyQueryMsg::Value { address } = msg;
+    let denom = MARKET_DENOM.load(deps.storage)?;
+    let total_shares = MARKET_TOTAL_SHARES.load(deps.storage)?;
+    let shares = MARKET_SHARES
+        .may_load(deps.storage, &deps.api.addr_validate(&address)?)?
+        .unwrap_or_default();
+    let held = deps
+        .querier
+        .query_balance(&env.contract.address, &denom)?
+        .amount;
+    let amount = if total_shares.is_zero() {
+        Uint128::zero()
+    } else {
+        held.multiply_ratio(shares, total_shares)
+    };
+    to_binary(&ValueResponse {
+        value: Coin { denom, amount },
+    })
+}
+
+pub fn mock_money_market() -> Box<dyn Contract<Empty>> {
+    Box::new(
+        ContractWrapper::new(market_execute, market_instantiate, market_query)
+            .with_sudo(market_sudo),
+    )
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairInstantiateMsg {
+    pub denoms: [String; 2],
+    // Taken from the output after the spread check, as Astroport does
+    pub commission: Decimal,
+}
+
+// Constant product pool, the reserves are its balances
+const PAIR_DENOMS: Item<[String; 2]> = Item::new("denoms");
+const PAIR_COMMISSION: Item<Decimal> = Item::new("commission");
+
+fn pair_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: PairInstantiateMsg,
+) -> StdResult<Response> {
+    PAIR_DENOMS.save(deps.storage, &msg.denoms)?;
+    PAIR_COMMISSION.save(deps.storage, &msg.commission)?;
+    Ok(Response::new())
+}
+
+fn pair_execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: PairExecuteMsg,
+) -> StdResult<Response> {
+    let PairExecuteMsg::Swap {
+        offer_asset,
+        belief_price,
+        max_spread,
+        to,
+    } = msg;
+    let offer = offer_asset.amount;
+    let [first, second] = PAIR_DENOMS.load(deps.storage)?;
+    let offer_denom = match offer_asset.info {
+        AssetInfo::NativeToken { denom } if denom == first || denom == second => denom,
+        _ => return Err(StdError::generic_err("Asset not in the pool")),
+    };
+    let ask_denom = if offer_denom == first { second } else { first };
+    if !info
+        .funds
+        .iter()
+        .any(|coin| coin.denom == offer_denom && coin.amount == offer)
+    {
+        return Err(StdError::generic_err("Offer not attached"));
+    }
+
+    // the offer is already part of the balance
+    let offer_pool = deps
+        .querier
+        .query_balance(&env.contract.address, &offer_denom)?
+        .amount
+        .checked_sub(offer)?;
+    let ask_pool = deps
+        .querier
+        .query_balance(&env.contract.address, &ask_denom)?
+        .amount;
+    let kept = offer_pool.multiply_ratio(ask_pool, offer_pool.checked_add(offer)?);
+    let amount = ask_pool.checked_sub(kept)?;
+    if let (Some(belief_price), Some(max_spread)) = (belief_price, max_spread) {
+        let expected = offer * (Decimal::one() / belief_price);
+        if amount < expected * (Decimal::one() - max_spread) {
+            return Err(StdError::generic_err("Operation exceeds max spread limit"));
+        }
+    }
+    let amount = amount.checked_sub(amount * PAIR_COMMISSION.load(deps.storage)?)?;
+    Ok(Response::new().add_message(BankMsg::Send {
+        to_address: to.unwrap_or_else(|| info.sender.to_string()),
+        amount: vec![Coin {
+            denom: ask_denom,
+            amount,
+        }],
+    }))
+}
+
+fn pair_query(deps: Deps, env: Env, msg: PairQueryMsg) -> StdResult<Binary> {
+    let PairQueryMsg::Pool {} = msg;
+    let assets = Vec::from(PAIR_DENOMS.load(deps.storage)?)
+        .into_iter()
+        .map(|denom| {
+            let amount = deps
+                .querier
+                .query_balance(&env.contract.address, &denom)?
+                .amount;
+            Ok(Asset {
+                info: AssetInfo::NativeToken { denom },
+                amount,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    to_binary(&PoolResponse {
+        assets,
+        total_share: Uint128::zero(),
+    })
+}
+
+pub fn mock_pair() -> Box<dyn Contract<Empty>> {
+    Box::new(ContractWrapper::new(
+        pair_execute,
+        pair_instantiate,
+        pair_query,
+    ))
+}